@@ -1,34 +1,97 @@
-use std::error::Error;
+use std::{error::Error, fs::File, io::BufReader};
 
-use advent_utils::{get_config, read_file, Part};
+use serde::Deserialize;
+
+use advent_utils::{get_custom_config, read_file, Part};
 
 mod part_one;
 mod part_two;
+mod polymer;
 mod reactor;
+mod rules;
 
-fn main() -> Result<(), Box<dyn Error>> {
-    let config = get_config()?;
+use self::polymer::parse_polymer;
+use self::reactor::DEFAULT_CHUNK_SIZE;
+use self::rules::{PairTable, Polarity, ReactionRule};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum ReactorMode {
+    #[default]
+    Stream,
+    Chunked,
+}
 
-    let polymer = read_file(&config.input_file)?;
-    let polymer = polymer.trim_end().chars();
+#[derive(Debug, Deserialize)]
+struct Config {
+    part: Part,
+    input_file: String,
+    /// Reacting pairs like `"()"` or `"xy"`, puzzle polarity rule is used if empty
+    #[serde(default)]
+    reaction_pairs: Vec<String>,
+    #[serde(default)]
+    reactor: ReactorMode,
+    chunk_size: Option<usize>,
+    /// Skip whitespace inside the polymer instead of reporting it as invalid units
+    #[serde(default)]
+    strip_whitespace: bool,
+}
 
+fn solve(config: &Config, rule: &(impl ReactionRule + Sync)) -> Result<(), Box<dyn Error>> {
     match config.part {
         Part::One => {
-            println!(
-                "Resulting polymer length is {}",
-                part_one::perform_reactions(polymer).len()
-            );
+            let polymer = match config.reactor {
+                ReactorMode::Stream => {
+                    let input = BufReader::new(File::open(&config.input_file)?);
+
+                    part_one::reduce_stream(input, rule, config.strip_whitespace)?
+                }
+                ReactorMode::Chunked => {
+                    let input = read_file(&config.input_file)?;
+                    let polymer = parse_polymer(&input, rule, config.strip_whitespace)?;
+                    let chunk_size = config.chunk_size.unwrap_or(DEFAULT_CHUNK_SIZE);
+
+                    part_one::reduce_chunked(&polymer, chunk_size, rule)
+                }
+            };
+
+            println!("Resulting polymer length is {}", polymer.len());
         }
         Part::Two => {
-            let (bad, poly) = part_two::find_bad_unit(&polymer);
+            let input = read_file(&config.input_file)?;
+            let polymer = parse_polymer(&input, rule, config.strip_whitespace)?;
 
-            println!(
-                "Bad unit is {}, resulting polymer length is {}",
-                bad,
-                poly.len()
-            );
+            match part_two::find_bad_classes(&polymer, rule) {
+                Some((bad, len)) => println!(
+                    "Bad units are {}, resulting polymer length is {}",
+                    bad.into_iter()
+                        .map(char::from)
+                        .map(String::from)
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    len
+                ),
+                None => println!("Polymer is empty"),
+            }
         }
     }
 
     Ok(())
 }
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let config = get_custom_config::<Config>()?;
+
+    if config.reaction_pairs.is_empty() {
+        solve(&config, &Polarity)
+    } else {
+        let table = PairTable::from_pairs(&config.reaction_pairs)?;
+
+        if config.reactor == ReactorMode::Chunked {
+            // chunks are merged in arbitrary order, which only matches streaming for confluent rules
+            table.check_confluent()?;
+        }
+
+        solve(&config, &table)
+    }
+}
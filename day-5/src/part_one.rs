@@ -1 +1 @@
-pub(crate) use crate::reactor::perform_reactions;
+pub(crate) use crate::reactor::{reduce_chunked, reduce_stream};
@@ -1,45 +1,130 @@
-use crate::reactor::perform_reactions;
+use std::collections::BTreeSet;
 
-fn filter_polymer(polymer: impl Iterator<Item = char>, c: char) -> impl Iterator<Item = char> {
-    polymer.filter(move |ch| !ch.eq_ignore_ascii_case(&c))
+use crate::{reactor::reduce_in_place, rules::ReactionRule};
+
+fn filter_polymer<'a, R: ReactionRule>(
+    polymer: &'a [u8],
+    rule: &'a R,
+    class: u8,
+) -> impl Iterator<Item = u8> + 'a {
+    polymer
+        .iter()
+        .cloned()
+        .filter(move |unit| rule.class_of(*unit) != class)
 }
 
-pub(crate) fn find_bad_unit(polymer: &(impl Iterator<Item = char> + Clone)) -> (char, String) {
-    let chars = b'a'..=b'z';
+/// Find symbol classes, whose removal gives the shortest reduced polymer.
+///
+/// All classes tied for the minimum are returned in ascending order, `None` for an empty polymer.
+pub(crate) fn find_bad_classes(
+    polymer: &[u8],
+    rule: &impl ReactionRule,
+) -> Option<(Vec<u8>, usize)> {
+    let mut buffer = Vec::with_capacity(polymer.len());
+    let classes = polymer
+        .iter()
+        .map(|unit| rule.class_of(*unit))
+        .collect::<BTreeSet<_>>();
+
+    let mut best: Option<(Vec<u8>, usize)> = None;
 
-    chars
-        .map(char::from)
-        .map(|c| (c, perform_reactions(filter_polymer(polymer.clone(), c))))
-        .min_by_key(|(_, polymer)| polymer.len())
-        .unwrap() // safe because initial set is statically non-empty
+    for class in classes {
+        buffer.clear();
+        buffer.extend(filter_polymer(polymer, rule, class));
+        let len = reduce_in_place(&mut buffer, rule);
+
+        match &mut best {
+            Some((bad, min_len)) if len == *min_len => bad.push(class),
+            Some((_, min_len)) if len > *min_len => {}
+            _ => best = Some((vec![class], len)),
+        }
+    }
+
+    best
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    use crate::{
+        reactor::perform_reactions,
+        rules::{PairTable, Polarity},
+    };
+
     #[test]
     fn test_filter() {
         macro_rules! test_filter {
-            ($input:expr, $ch: expr, $expected:expr) => {
+            ($input:expr, $unit: expr, $expected:expr) => {
                 assert_eq!(
-                    filter_polymer($input.chars(), $ch).collect::<String>(),
-                    $expected
+                    filter_polymer($input.as_bytes(), &Polarity, Polarity.class_of($unit))
+                        .collect::<Vec<_>>(),
+                    $expected.as_bytes()
                 );
             };
         }
 
-        test_filter!("dabAcCaCBAcCcaDA", 'a', "dbcCCBcCcD");
-        test_filter!("dabAcCaCBAcCcaDA", 'b', "daAcCaCAcCcaDA");
-        test_filter!("dabAcCaCBAcCcaDA", 'C', "dabAaBAaDA");
-        test_filter!("dabAcCaCBAcCcaDA", 'D', "abAcCaCBAcCcaA");
+        test_filter!("dabAcCaCBAcCcaDA", b'a', "dbcCCBcCcD");
+        test_filter!("dabAcCaCBAcCcaDA", b'b', "daAcCaCAcCcaDA");
+        test_filter!("dabAcCaCBAcCcaDA", b'C', "dabAaBAaDA");
+        test_filter!("dabAcCaCBAcCcaDA", b'D', "abAcCaCBAcCcaA");
     }
 
     #[test]
     fn test_finder() {
         assert_eq!(
-            find_bad_unit(&"dabAcCaCBAcCcaDA".chars()),
-            ('c', "daDA".to_owned())
+            find_bad_classes(b"dabAcCaCBAcCcaDA", &Polarity),
+            Some((vec![b'c'], 4))
+        );
+        assert_eq!(find_bad_classes(b"", &Polarity), None);
+    }
+
+    #[test]
+    fn test_finder_ties() {
+        assert_eq!(
+            find_bad_classes(b"aBbAcDdC", &Polarity),
+            Some((vec![b'a', b'b', b'c', b'd'], 0))
+        );
+        assert_eq!(
+            find_bad_classes(b"dAaBbC", &Polarity),
+            Some((vec![b'c', b'd'], 1))
+        );
+    }
+
+    #[test]
+    fn test_finder_matches_reference() {
+        let polymer = "dabAcCaCBAcCcaDAzZbBqQaAdD";
+
+        let lengths = (b'a'..=b'z')
+            .filter(|unit| polymer.bytes().any(|u| u.eq_ignore_ascii_case(unit)))
+            .map(|unit| {
+                let c = char::from(unit);
+                let filtered = polymer.chars().filter(|ch| !ch.eq_ignore_ascii_case(&c));
+
+                (unit, perform_reactions(filtered).len())
+            })
+            .collect::<Vec<_>>();
+        let min_len = lengths.iter().map(|(_, len)| *len).min().unwrap();
+        let bad = lengths
+            .iter()
+            .filter(|(_, len)| *len == min_len)
+            .map(|(unit, _)| *unit)
+            .collect();
+
+        assert_eq!(
+            find_bad_classes(polymer.as_bytes(), &Polarity),
+            Some((bad, min_len))
+        );
+    }
+
+    #[test]
+    fn test_finder_pair_table() {
+        let brackets = PairTable::from_pairs(&["()", "[]"]).unwrap();
+
+        // removing the stray square bracket lets all round ones annihilate
+        assert_eq!(
+            find_bad_classes(b"((([)))", &brackets),
+            Some((vec![b'['], 0))
         );
     }
 }
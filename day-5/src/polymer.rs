@@ -0,0 +1,277 @@
+use std::{error::Error, fmt, io, str};
+
+use crate::rules::ReactionRule;
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct InvalidUnit {
+    /// Byte offset of the unit in the input
+    pub position: usize,
+    pub unit: char,
+}
+
+impl fmt::Display for InvalidUnit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?} at {}", self.unit, self.position)
+    }
+}
+
+#[derive(Debug)]
+pub(crate) enum PolymerError {
+    Io(io::Error),
+    InvalidUnits(Vec<InvalidUnit>),
+}
+
+impl fmt::Display for PolymerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PolymerError::Io(error) => write!(f, "failed to read polymer: {}", error),
+            PolymerError::InvalidUnits(units) => {
+                write!(f, "invalid polymer units: ")?;
+
+                for (i, unit) in units.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", unit)?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+impl From<io::Error> for PolymerError {
+    fn from(error: io::Error) -> Self {
+        PolymerError::Io(error)
+    }
+}
+
+impl Error for PolymerError {}
+
+/// Byte-by-byte polymer validator, usable both on in-memory and streamed input.
+///
+/// Line breaks and trailing whitespace are always skipped, other whitespace only if
+/// `strip_whitespace` is set; a run of whitespace is reported by its first unit.
+pub(crate) struct UnitDecoder<'r, R> {
+    rule: &'r R,
+    strip_whitespace: bool,
+    position: usize,
+    // incomplete UTF-8 sequence and its starting offset
+    pending: Vec<u8>,
+    pending_at: usize,
+    // first unit of the whitespace run, which is only invalid if something follows it
+    whitespace: Option<InvalidUnit>,
+    invalid_units: Vec<InvalidUnit>,
+}
+
+impl<'r, R: ReactionRule> UnitDecoder<'r, R> {
+    pub(crate) fn new(rule: &'r R, strip_whitespace: bool) -> Self {
+        Self {
+            rule,
+            strip_whitespace,
+            position: 0,
+            pending: Vec::new(),
+            pending_at: 0,
+            whitespace: None,
+            invalid_units: Vec::new(),
+        }
+    }
+
+    fn classify(&mut self, position: usize, unit: char) -> Option<u8> {
+        if unit == '\n' || unit == '\r' || (self.strip_whitespace && unit.is_whitespace()) {
+            return None;
+        }
+
+        if unit.is_whitespace() {
+            if self.whitespace.is_none() {
+                self.whitespace = Some(InvalidUnit { position, unit });
+            }
+
+            return None;
+        }
+
+        self.invalid_units.extend(self.whitespace.take());
+
+        if unit.is_ascii() && self.rule.is_unit(unit as u8) {
+            Some(unit as u8)
+        } else {
+            self.invalid_units.push(InvalidUnit { position, unit });
+
+            None
+        }
+    }
+
+    fn flush_pending(&mut self) {
+        if !self.pending.is_empty() {
+            self.pending.clear();
+            self.invalid_units.extend(self.whitespace.take());
+            self.invalid_units.push(InvalidUnit {
+                position: self.pending_at,
+                unit: char::REPLACEMENT_CHARACTER,
+            });
+        }
+    }
+
+    /// Feed next input byte, returning it back if it is a valid unit
+    pub(crate) fn feed(&mut self, byte: u8) -> Option<u8> {
+        let position = self.position;
+        self.position += 1;
+
+        if byte.is_ascii() {
+            self.flush_pending();
+
+            return self.classify(position, char::from(byte));
+        }
+
+        if self.pending.is_empty() {
+            self.pending_at = position;
+        }
+        self.pending.push(byte);
+
+        match str::from_utf8(&self.pending) {
+            Ok(decoded) => {
+                let unit = decoded.chars().next().unwrap(); // safe because pending is non-empty
+                self.pending.clear();
+
+                self.classify(self.pending_at, unit)
+            }
+            Err(error) if error.error_len().is_some() => {
+                self.flush_pending();
+
+                None
+            }
+            Err(_) => None,
+        }
+    }
+
+    pub(crate) fn finish(mut self) -> Result<(), PolymerError> {
+        self.flush_pending();
+
+        if self.invalid_units.is_empty() {
+            Ok(())
+        } else {
+            Err(PolymerError::InvalidUnits(self.invalid_units))
+        }
+    }
+}
+
+/// Parse polymer, reporting every unit that is not valid under `rule`.
+pub(crate) fn parse_polymer(
+    input: &str,
+    rule: &impl ReactionRule,
+    strip_whitespace: bool,
+) -> Result<Vec<u8>, PolymerError> {
+    let mut decoder = UnitDecoder::new(rule, strip_whitespace);
+    let polymer = input
+        .bytes()
+        .filter_map(|byte| decoder.feed(byte))
+        .collect();
+
+    decoder.finish()?;
+
+    Ok(polymer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::rules::{PairTable, Polarity};
+
+    fn invalid_units(result: Result<Vec<u8>, PolymerError>) -> Vec<(usize, char)> {
+        match result {
+            Err(PolymerError::InvalidUnits(units)) => units
+                .into_iter()
+                .map(|invalid| (invalid.position, invalid.unit))
+                .collect(),
+            other => panic!("expected invalid units, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_valid() {
+        assert_eq!(
+            parse_polymer("dabAcCaCBAcCcaDA\n", &Polarity, false).unwrap(),
+            b"dabAcCaCBAcCcaDA"
+        );
+        assert_eq!(parse_polymer("", &Polarity, false).unwrap(), b"");
+        assert_eq!(
+            parse_polymer("ab\r\ncd", &Polarity, false).unwrap(),
+            b"abcd"
+        );
+        assert_eq!(parse_polymer("ab \t\n", &Polarity, false).unwrap(), b"ab");
+        assert_eq!(
+            parse_polymer("ab\u{a0} \n", &Polarity, false).unwrap(),
+            b"ab"
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid() {
+        assert_eq!(
+            invalid_units(parse_polymer("ab1c d", &Polarity, false)),
+            vec![(2, '1'), (4, ' ')]
+        );
+        // whitespace run is reported by its first unit as soon as anything follows it
+        assert_eq!(
+            invalid_units(parse_polymer("ab \t1 ", &Polarity, false)),
+            vec![(2, ' '), (4, '1')]
+        );
+        let long_run = format!("a{}b", " ".repeat(100_000));
+        assert_eq!(
+            invalid_units(parse_polymer(&long_run, &Polarity, false)),
+            vec![(1, ' ')]
+        );
+        assert_eq!(
+            invalid_units(parse_polymer("a \u{ff}", &Polarity, false)),
+            vec![(1, ' '), (2, 'ÿ')]
+        );
+        assert_eq!(
+            invalid_units(parse_polymer("aéB\u{fc}", &Polarity, false)),
+            vec![(1, 'é'), (4, 'ü')]
+        );
+    }
+
+    #[test]
+    fn test_parse_strip_whitespace() {
+        assert_eq!(
+            parse_polymer("ab c\td\u{a0}e", &Polarity, true).unwrap(),
+            b"abcde"
+        );
+        assert_eq!(
+            invalid_units(parse_polymer("a b-c", &Polarity, true)),
+            vec![(3, '-')]
+        );
+    }
+
+    #[test]
+    fn test_parse_pair_table() {
+        let brackets = PairTable::from_pairs(&["()"]).unwrap();
+
+        assert_eq!(parse_polymer("(a1)", &brackets, false).unwrap(), b"(a1)");
+        assert_eq!(
+            invalid_units(parse_polymer("( )", &brackets, false)),
+            vec![(1, ' ')]
+        );
+    }
+
+    #[test]
+    fn test_decoder_broken_utf8() {
+        let mut decoder = UnitDecoder::new(&Polarity, false);
+        let units = [b'a', 0xc3, b'b', 0xff, 0xe2, 0x82]
+            .iter()
+            .filter_map(|byte| decoder.feed(*byte))
+            .collect::<Vec<_>>();
+
+        assert_eq!(units, b"ab");
+        assert_eq!(
+            invalid_units(decoder.finish().map(|_| units)),
+            vec![
+                (1, char::REPLACEMENT_CHARACTER),
+                (3, char::REPLACEMENT_CHARACTER),
+                (4, char::REPLACEMENT_CHARACTER),
+            ]
+        );
+    }
+}
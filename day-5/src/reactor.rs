@@ -1,7 +1,21 @@
+use std::io::BufRead;
+
+use rayon::prelude::*;
+
+use crate::{
+    polymer::{PolymerError, UnitDecoder},
+    rules::ReactionRule,
+};
+
+pub(crate) const DEFAULT_CHUNK_SIZE: usize = 64 * 1024;
+
+#[cfg(test)]
 fn should_react(left: char, right: char) -> bool {
-    (left.is_ascii_lowercase() ^ right.is_ascii_lowercase() && left.eq_ignore_ascii_case(&right))
+    left.is_ascii_lowercase() ^ right.is_ascii_lowercase() && left.eq_ignore_ascii_case(&right)
 }
 
+/// Reference char-based implementation, used to check the byte-oriented reactors.
+#[cfg(test)]
 pub(crate) fn perform_reactions(polymer: impl Iterator<Item = char>) -> String {
     let mut stack = Vec::new();
 
@@ -21,10 +35,128 @@ pub(crate) fn perform_reactions(polymer: impl Iterator<Item = char>) -> String {
     stack.iter().collect()
 }
 
+/// Reduce polymer in place, using the start of the buffer as a stack.
+///
+/// Returns length of the reduced polymer, which occupies `polymer[..len]`.
+pub(crate) fn reduce_in_place(polymer: &mut [u8], rule: &impl ReactionRule) -> usize {
+    let mut len = 0;
+
+    for i in 0..polymer.len() {
+        let unit = polymer[i];
+
+        if len > 0 && rule.reacts(polymer[len - 1], unit) {
+            len -= 1;
+        } else {
+            polymer[len] = unit;
+            len += 1;
+        }
+    }
+
+    len
+}
+
+/// Reduce polymer read from `reader`, holding only the current stack in memory.
+///
+/// Input is validated on the fly, see `UnitDecoder` for the rules.
+pub(crate) fn reduce_stream<R: ReactionRule>(
+    mut reader: impl BufRead,
+    rule: &R,
+    strip_whitespace: bool,
+) -> Result<Vec<u8>, PolymerError> {
+    let mut decoder = UnitDecoder::new(rule, strip_whitespace);
+    let mut stack = Vec::new();
+
+    loop {
+        let consumed = {
+            let buf = reader.fill_buf()?;
+
+            if buf.is_empty() {
+                break;
+            }
+
+            for unit in buf.iter().filter_map(|byte| decoder.feed(*byte)) {
+                match stack.last() {
+                    Some(&other_unit) if rule.reacts(other_unit, unit) => {
+                        stack.pop();
+                    }
+                    _ => stack.push(unit),
+                }
+            }
+
+            buf.len()
+        };
+
+        reader.consume(consumed);
+    }
+
+    decoder.finish()?;
+
+    Ok(stack)
+}
+
+/// Join two reduced polymers: since neither has internal reactions, they can only react at the seam.
+fn merge_reduced(mut left: Vec<u8>, right: Vec<u8>, rule: &impl ReactionRule) -> Vec<u8> {
+    let mut seam = 0;
+
+    while seam < right.len() {
+        match left.last() {
+            Some(&unit) if rule.reacts(unit, right[seam]) => {
+                left.pop();
+                seam += 1;
+            }
+            _ => break,
+        }
+    }
+
+    left.extend_from_slice(&right[seam..]);
+
+    left
+}
+
+/// Reduce polymer by splitting it into chunks, reducing them in parallel and merging neighbours.
+///
+/// Gives the same result as the sequential reactors as long as the rule is confluent
+/// (if both `ab` and `bc` react, then `a == c`), which holds for the puzzle polarity rule
+/// and is checked by `PairTable::check_confluent` for custom ones.
+pub(crate) fn reduce_chunked<R>(polymer: &[u8], chunk_size: usize, rule: &R) -> Vec<u8>
+where
+    R: ReactionRule + Sync,
+{
+    polymer
+        .par_chunks(chunk_size.max(1))
+        .map(|chunk| {
+            let mut chunk = chunk.to_vec();
+            let len = reduce_in_place(&mut chunk, rule);
+            chunk.truncate(len);
+
+            chunk
+        })
+        .reduce(Vec::new, |left, right| merge_reduced(left, right, rule))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    use std::io;
+
+    use crate::{
+        polymer::InvalidUnit,
+        rules::{PairTable, Polarity},
+    };
+
+    fn reduce_bytes(polymer: &str) -> String {
+        let mut buffer = polymer.as_bytes().to_vec();
+        let len = reduce_in_place(&mut buffer, &Polarity);
+        buffer.truncate(len);
+
+        String::from_utf8(buffer).unwrap()
+    }
+
+    fn reduce_reader(polymer: &str) -> String {
+        String::from_utf8(reduce_stream(polymer.as_bytes(), &Polarity, false).unwrap()).unwrap()
+    }
+
     #[test]
     fn test_reaction_checker() {
         assert!(should_react('a', 'A'));
@@ -39,6 +171,21 @@ mod tests {
         assert!(!should_react('B', 'a'));
     }
 
+    #[test]
+    fn test_bytes_reaction_checker() {
+        for left in 0..=127u8 {
+            for right in 0..=127u8 {
+                assert_eq!(
+                    Polarity.reacts(left, right),
+                    should_react(char::from(left), char::from(right)),
+                    "{:?} {:?}",
+                    char::from(left),
+                    char::from(right),
+                );
+            }
+        }
+    }
+
     #[test]
     fn test_reactor() {
         assert_eq!(perform_reactions("aA".chars()), "");
@@ -46,4 +193,134 @@ mod tests {
         assert_eq!(perform_reactions("abAB".chars()), "abAB");
         assert_eq!(perform_reactions("aabAAB".chars()), "aabAAB");
     }
+
+    #[test]
+    fn test_in_place_reactor() {
+        assert_eq!(reduce_bytes("aA"), "");
+        assert_eq!(reduce_bytes("abBA"), "");
+        assert_eq!(reduce_bytes("abAB"), "abAB");
+        assert_eq!(reduce_bytes("aabAAB"), "aabAAB");
+        assert_eq!(reduce_bytes("dabAcCaCBAcCcaDA"), "dabCBAcaDA");
+    }
+
+    #[test]
+    fn test_stream_reactor() {
+        assert_eq!(reduce_reader("aA"), "");
+        assert_eq!(reduce_reader("abBA\n"), "");
+        assert_eq!(reduce_reader("abAB\r\n"), "abAB");
+        assert_eq!(reduce_reader("aabAAB"), "aabAAB");
+        assert_eq!(reduce_reader("dabAcCaCBAcCcaDA\n"), "dabCBAcaDA");
+
+        // reactions across buffer boundaries
+        let reader = io::BufReader::with_capacity(3, "dabAcCaCBAcCcaDA".as_bytes());
+        assert_eq!(
+            reduce_stream(reader, &Polarity, false).unwrap(),
+            b"dabCBAcaDA"
+        );
+
+        match reduce_stream(&b"ab1A\n"[..], &Polarity, false) {
+            Err(PolymerError::InvalidUnits(units)) => assert_eq!(units.len(), 1),
+            other => panic!("expected invalid units, got {:?}", other),
+        }
+        assert_eq!(reduce_stream(&b"ab B A"[..], &Polarity, true).unwrap(), b"");
+
+        // trailing whitespace is skipped the same way as by `parse_polymer`
+        assert_eq!(
+            reduce_stream(&b"ab \n"[..], &Polarity, false).unwrap(),
+            b"ab"
+        );
+        let reader = io::BufReader::with_capacity(2, &b"aA  b \t\r\n"[..]);
+        match reduce_stream(reader, &Polarity, false) {
+            Err(PolymerError::InvalidUnits(units)) => assert_eq!(
+                units[..],
+                [InvalidUnit {
+                    position: 2,
+                    unit: ' '
+                }]
+            ),
+            other => panic!("expected invalid units, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_merge_reduced() {
+        assert_eq!(
+            merge_reduced(b"abC".to_vec(), b"cBd".to_vec(), &Polarity),
+            b"ad"
+        );
+        assert_eq!(
+            merge_reduced(b"ab".to_vec(), b"BA".to_vec(), &Polarity),
+            b""
+        );
+        assert_eq!(
+            merge_reduced(b"ab".to_vec(), b"AB".to_vec(), &Polarity),
+            b"abAB"
+        );
+        assert_eq!(merge_reduced(Vec::new(), b"aB".to_vec(), &Polarity), b"aB");
+        assert_eq!(merge_reduced(b"aB".to_vec(), Vec::new(), &Polarity), b"aB");
+    }
+
+    #[test]
+    fn test_chunked_reactor() {
+        let polymer = b"dabAcCaCBAcCcaDA";
+
+        for chunk_size in 0..=polymer.len() + 1 {
+            assert_eq!(
+                reduce_chunked(polymer, chunk_size, &Polarity),
+                b"dabCBAcaDA",
+                "chunk size {}",
+                chunk_size
+            );
+        }
+
+        assert_eq!(reduce_chunked(b"", 4, &Polarity), b"");
+    }
+
+    #[test]
+    fn test_pair_table_reactor() {
+        let brackets = PairTable::from_pairs(&["()", "[]", "{}"]).unwrap();
+
+        let mut polymer = b"([]{()})".to_vec();
+        assert_eq!(reduce_in_place(&mut polymer, &brackets), 0);
+
+        let mut polymer = b"([)]".to_vec();
+        assert_eq!(reduce_in_place(&mut polymer, &brackets), 4);
+
+        let mut polymer = b")(a(b)".to_vec();
+        let len = reduce_in_place(&mut polymer, &brackets);
+        assert_eq!(&polymer[..len], b")(a(b)");
+
+        let mutual = PairTable::from_pairs(&["xy", "yx"]).unwrap();
+        assert_eq!(
+            reduce_stream(&b"xyyxzyx\n"[..], &mutual, false).unwrap(),
+            b"z".to_vec()
+        );
+    }
+
+    #[test]
+    fn test_reactors_agree() {
+        // simple LCG to get reproducible pseudo-random polymers
+        let mut state = 0x2018_u32;
+        let mut next_unit = || {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            b"aAbBcC"[(state >> 16) as usize % 6]
+        };
+
+        for len in 0..200 {
+            let polymer = (0..len)
+                .map(|_| char::from(next_unit()))
+                .collect::<String>();
+            let expected = perform_reactions(polymer.chars());
+
+            assert_eq!(reduce_bytes(&polymer), expected);
+            assert_eq!(reduce_reader(&polymer), expected);
+
+            for &chunk_size in &[1, 2, 3, 7, 64] {
+                assert_eq!(
+                    reduce_chunked(polymer.as_bytes(), chunk_size, &Polarity),
+                    expected.as_bytes()
+                );
+            }
+        }
+    }
 }
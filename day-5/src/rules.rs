@@ -0,0 +1,257 @@
+use std::{error::Error, fmt};
+
+const N_SYMBOLS: usize = 256;
+
+pub(crate) trait ReactionRule {
+    /// Whether `right` annihilates with `left` when placed right after it
+    fn reacts(&self, left: u8, right: u8) -> bool;
+
+    /// Symbol class of the unit: units of one class are removed together in part two
+    fn class_of(&self, unit: u8) -> u8;
+
+    /// Whether symbol is allowed to appear in the polymer
+    fn is_unit(&self, unit: u8) -> bool;
+}
+
+/// Puzzle rule: same letter, opposite case.
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct Polarity;
+
+impl ReactionRule for Polarity {
+    fn reacts(&self, left: u8, right: u8) -> bool {
+        // ASCII letters differ from their other-case counterpart only in the 0x20 bit
+        left ^ right == 0x20 && left.is_ascii_alphabetic()
+    }
+
+    fn class_of(&self, unit: u8) -> u8 {
+        unit.to_ascii_lowercase()
+    }
+
+    fn is_unit(&self, unit: u8) -> bool {
+        unit.is_ascii_alphabetic()
+    }
+}
+
+/// Explicit table of reacting `(left, right)` pairs.
+///
+/// Symbols linked by pairs form one class, represented by the smallest of them.
+#[derive(Clone)]
+pub(crate) struct PairTable {
+    reacts: Vec<bool>,
+    classes: [u8; N_SYMBOLS],
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum PairTableError {
+    InvalidPair(String),
+    /// Three symbols, that reduce to either the first or the last one
+    NotConfluent(String),
+}
+
+impl fmt::Display for PairTableError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PairTableError::InvalidPair(pair) => write!(
+                f,
+                "invalid reaction pair {:?}: expected exactly two printable ASCII symbols",
+                pair
+            ),
+            PairTableError::NotConfluent(units) => write!(
+                f,
+                "reaction pairs are not confluent: {:?} reduces to either {:?} or {:?}",
+                units,
+                &units[units.len() - 1..],
+                &units[..1]
+            ),
+        }
+    }
+}
+
+impl Error for PairTableError {}
+
+impl PairTable {
+    pub(crate) fn new() -> Self {
+        let mut classes = [0; N_SYMBOLS];
+        for (symbol, class) in classes.iter_mut().enumerate() {
+            *class = symbol as u8;
+        }
+
+        Self {
+            reacts: vec![false; N_SYMBOLS * N_SYMBOLS],
+            classes,
+        }
+    }
+
+    pub(crate) fn add_pair(&mut self, left: u8, right: u8) {
+        self.reacts[usize::from(left) * N_SYMBOLS + usize::from(right)] = true;
+
+        let (from, to) = (
+            self.classes[usize::from(left)],
+            self.classes[usize::from(right)],
+        );
+        let (from, to) = if from < to { (to, from) } else { (from, to) };
+
+        for class in self.classes.iter_mut().filter(|class| **class == from) {
+            *class = to;
+        }
+    }
+
+    /// Build table from pairs written as two-symbol strings, e.g. `"()"` or `"xy"`
+    pub(crate) fn from_pairs<S: AsRef<str>>(pairs: &[S]) -> Result<Self, PairTableError> {
+        let mut table = Self::new();
+
+        for pair in pairs {
+            let pair = pair.as_ref();
+
+            match pair.as_bytes() {
+                &[left, right] if left.is_ascii_graphic() && right.is_ascii_graphic() => {
+                    table.add_pair(left, right)
+                }
+                _ => return Err(PairTableError::InvalidPair(pair.to_owned())),
+            }
+        }
+
+        Ok(table)
+    }
+
+    fn pairs(&self) -> Vec<(u8, u8)> {
+        (0..N_SYMBOLS * N_SYMBOLS)
+            .filter(|idx| self.reacts[*idx])
+            .map(|idx| ((idx / N_SYMBOLS) as u8, (idx % N_SYMBOLS) as u8))
+            .collect()
+    }
+
+    /// Check, that the reduced polymer doesn't depend on the order of reactions.
+    ///
+    /// If both `ab` and `bc` react, `abc` reduces to either `c` or `a`, so these have to be
+    /// the same symbol; since reactions always shorten the polymer, that is enough.
+    pub(crate) fn check_confluent(&self) -> Result<(), PairTableError> {
+        let pairs = self.pairs();
+
+        for &(left, middle) in &pairs {
+            for &(_, right) in pairs.iter().filter(|(first, _)| *first == middle) {
+                if left != right {
+                    let units = [left, middle, right]
+                        .iter()
+                        .map(|&unit| char::from(unit))
+                        .collect();
+
+                    return Err(PairTableError::NotConfluent(units));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Debug for PairTable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let pairs = self
+            .pairs()
+            .into_iter()
+            .map(|(left, right)| (char::from(left), char::from(right)))
+            .collect::<Vec<_>>();
+
+        f.debug_struct("PairTable").field("pairs", &pairs).finish()
+    }
+}
+
+impl ReactionRule for PairTable {
+    fn reacts(&self, left: u8, right: u8) -> bool {
+        self.reacts[usize::from(left) * N_SYMBOLS + usize::from(right)]
+    }
+
+    fn class_of(&self, unit: u8) -> u8 {
+        self.classes[usize::from(unit)]
+    }
+
+    fn is_unit(&self, unit: u8) -> bool {
+        unit.is_ascii_graphic()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_polarity() {
+        assert!(Polarity.reacts(b'a', b'A'));
+        assert!(Polarity.reacts(b'Z', b'z'));
+        assert!(!Polarity.reacts(b'a', b'a'));
+        assert!(!Polarity.reacts(b'a', b'B'));
+        assert!(!Polarity.reacts(b'@', b'`'));
+
+        assert_eq!(Polarity.class_of(b'C'), b'c');
+        assert_eq!(Polarity.class_of(b'c'), b'c');
+
+        assert!(Polarity.is_unit(b'q'));
+        assert!(Polarity.is_unit(b'Q'));
+        assert!(!Polarity.is_unit(b'1'));
+        assert!(!Polarity.is_unit(b' '));
+    }
+
+    #[test]
+    fn test_pair_table() {
+        let table = PairTable::from_pairs(&["()", "[]", "xy", "yx"]).unwrap();
+
+        assert!(table.reacts(b'(', b')'));
+        assert!(!table.reacts(b')', b'('));
+        assert!(table.reacts(b'x', b'y'));
+        assert!(table.reacts(b'y', b'x'));
+        assert!(!table.reacts(b'(', b']'));
+        assert!(!table.reacts(b'a', b'A'));
+
+        assert_eq!(table.class_of(b')'), b'(');
+        assert_eq!(table.class_of(b'('), b'(');
+        assert_eq!(table.class_of(b']'), b'[');
+        assert_eq!(table.class_of(b'y'), b'x');
+        assert_eq!(table.class_of(b'a'), b'a');
+    }
+
+    #[test]
+    fn test_pair_table_classes_merge() {
+        let table = PairTable::from_pairs(&["cd", "ab", "bc"]).unwrap();
+
+        for unit in b"abcd" {
+            assert_eq!(table.class_of(*unit), b'a');
+        }
+    }
+
+    #[test]
+    fn test_pair_table_errors() {
+        assert_eq!(
+            PairTable::from_pairs(&["()", "abc"]).unwrap_err(),
+            PairTableError::InvalidPair("abc".to_owned())
+        );
+        assert!(PairTable::from_pairs(&["x"]).is_err());
+        assert!(PairTable::from_pairs(&["xé"]).is_err());
+        assert!(PairTable::from_pairs(&["x "]).is_err());
+    }
+
+    #[test]
+    fn test_pair_table_confluence() {
+        for pairs in &[&["()", "[]", "{}"][..], &["xy", "yx"], &["aa"], &[]] {
+            assert_eq!(
+                PairTable::from_pairs(pairs).unwrap().check_confluent(),
+                Ok(())
+            );
+        }
+
+        let table = PairTable::from_pairs(&["xy", "yz"]).unwrap();
+        assert_eq!(
+            table.check_confluent(),
+            Err(PairTableError::NotConfluent("xyz".to_owned()))
+        );
+        assert_eq!(
+            table.check_confluent().unwrap_err().to_string(),
+            "reaction pairs are not confluent: \"xyz\" reduces to either \"z\" or \"x\""
+        );
+
+        assert!(PairTable::from_pairs(&["ab", "ba", "bc"])
+            .unwrap()
+            .check_confluent()
+            .is_err());
+    }
+}
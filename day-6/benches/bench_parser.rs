@@ -1,26 +1,49 @@
-use criterion::{criterion_group, criterion_main, Criterion, Fun};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
 
 use day_6_parser::{
     default_parse::parse_point as default_parse, nom_parse::parse_point as nom_parse,
+    parse_points_with,
 };
 
-fn parsers_valid(c: &mut Criterion) {
-    let nom_parser = Fun::new("nom", |b, i| b.iter(|| nom_parse(*i)));
-    let split_parser = Fun::new("split", |b, i| b.iter(|| default_parse(*i)));
-
-    let funcs = vec![nom_parser, split_parser];
-
-    c.bench_functions("Parsers (valid)", funcs, "123123213, 3212132312");
+const INPUT: &str = include_str!("../full.txt");
+
+fn parsers_line(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Parsers (line)");
+
+    for (name, line) in &[
+        ("valid", "123123213, 2112132312"),
+        ("flexible", " (-123123213 ;\t+2112132312) "),
+        ("invalid", "123434254, a5adfsaffdsfda321"),
+    ] {
+        if *name != "invalid" {
+            assert!(nom_parse(line).is_ok(), "nom rejects {:?}", line);
+            assert!(default_parse(line).is_ok(), "split rejects {:?}", line);
+        }
+
+        group.bench_with_input(BenchmarkId::new("nom", name), line, |b, i| {
+            b.iter(|| nom_parse(i))
+        });
+        group.bench_with_input(BenchmarkId::new("split", name), line, |b, i| {
+            b.iter(|| default_parse(i))
+        });
+    }
+
+    group.finish();
 }
 
-fn parsers_invalid(c: &mut Criterion) {
-    let nom_parser = Fun::new("nom", |b, i| b.iter(|| nom_parse(*i)));
-    let split_parser = Fun::new("split", |b, i| b.iter(|| default_parse(*i)));
+fn parsers_file(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Parsers (whole file)");
+    group.throughput(Throughput::Bytes(INPUT.len() as u64));
 
-    let funcs = vec![nom_parser, split_parser];
+    group.bench_function("nom", |b| {
+        b.iter(|| parse_points_with(INPUT, nom_parse).unwrap())
+    });
+    group.bench_function("split", |b| {
+        b.iter(|| parse_points_with(INPUT, default_parse).unwrap())
+    });
 
-    c.bench_functions("Parsers (invalid)", funcs, "123434254, a5adfsaffdsfda321");
+    group.finish();
 }
 
-criterion_group!(benches, parsers_valid, parsers_invalid);
+criterion_group!(benches, parsers_line, parsers_file);
 criterion_main!(benches);
@@ -1,24 +1,111 @@
-use std::{num::ParseIntError, str::FromStr};
+use std::str::FromStr;
 
-use super::Point;
+use super::{ParseError, ParseErrorKind, Point};
 
-#[derive(Debug, PartialEq)]
-pub enum ParseError {
-    TooFewArgs,
-    ParseError(ParseIntError),
+/// Hand-written scanner over the input bytes
+struct Scanner<'a> {
+    input: &'a [u8],
+    pos: usize,
 }
 
-impl From<ParseIntError> for ParseError {
-    fn from(err: ParseIntError) -> Self {
-        ParseError::ParseError(err)
+impl<'a> Scanner<'a> {
+    fn error(&self, kind: ParseErrorKind) -> ParseError {
+        ParseError::at_offset(self.pos, kind)
+    }
+
+    /// Skip spaces and tabs, returning whether anything was skipped
+    fn skip_whitespace(&mut self) -> bool {
+        let start = self.pos;
+
+        while let Some(b' ') | Some(b'\t') = self.input.get(self.pos) {
+            self.pos += 1;
+        }
+
+        self.pos > start
+    }
+
+    fn eat(&mut self, expected: &[u8]) -> bool {
+        match self.input.get(self.pos) {
+            Some(byte) if expected.contains(byte) => {
+                self.pos += 1;
+
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn number(&mut self) -> Result<i32, ParseError> {
+        let start = self.pos;
+
+        self.eat(b"+-");
+        let digits_start = self.pos;
+        while let Some(b'0'..=b'9') = self.input.get(self.pos) {
+            self.pos += 1;
+        }
+
+        if self.pos == digits_start {
+            self.pos = start;
+
+            return Err(self.error(ParseErrorKind::ExpectedNumber));
+        }
+
+        // slice is a sign followed by ASCII digits, so it's valid UTF-8 and only can overflow
+        std::str::from_utf8(&self.input[start..self.pos])
+            .unwrap()
+            .parse()
+            .map_err(|_| ParseError::at_offset(start, ParseErrorKind::NumberOutOfRange))
     }
 }
 
-pub fn parse_point(s: &str) -> Result<Point, ParseError> {
-    let mut splitted = s.split(", ");
+/// Parse `N` coordinates like `1, 2, 3`, `-1;+2;3`, `1 2 3` or `(1, 2, 3)`
+pub fn parse_coords<const N: usize>(s: &str) -> Result<[i32; N], ParseError> {
+    let mut scanner = Scanner {
+        input: s.as_bytes(),
+        pos: 0,
+    };
+
+    scanner.skip_whitespace();
+    let parenthesized = scanner.eat(b"(");
+    scanner.skip_whitespace();
+
+    let mut coords = [0; N];
+
+    for (i, coord) in coords.iter_mut().enumerate() {
+        if i > 0 {
+            let separator_at = scanner.pos;
+            let spaced = scanner.skip_whitespace();
+            if !scanner.eat(b",;") && !spaced {
+                return Err(ParseError::at_offset(
+                    separator_at,
+                    ParseErrorKind::ExpectedSeparator,
+                ));
+            }
+            scanner.skip_whitespace();
+        }
+
+        *coord = scanner.number()?;
+    }
+
+    scanner.skip_whitespace();
+
+    if parenthesized {
+        if !scanner.eat(b")") {
+            return Err(scanner.error(ParseErrorKind::UnclosedParenthesis));
+        }
+        scanner.skip_whitespace();
+    }
+
+    if scanner.pos != scanner.input.len() {
+        return Err(scanner.error(ParseErrorKind::TrailingCharacters));
+    }
+
+    Ok(coords)
+}
 
-    let x = splitted.next().ok_or(ParseError::TooFewArgs)?.parse()?;
-    let y = splitted.next().ok_or(ParseError::TooFewArgs)?.parse()?;
+/// Parse point like `1, 2`, `-1;+2`, `1 2` or `(1, 2)`
+pub fn parse_point(s: &str) -> Result<Point, ParseError> {
+    let [x, y] = parse_coords(s)?;
 
     Ok(Point::new(x, y))
 }
@@ -35,12 +122,90 @@ impl FromStr for Point {
 mod tests {
     use super::*;
 
+    fn error_at(column: usize, kind: ParseErrorKind) -> Result<Point, ParseError> {
+        Err(ParseError {
+            line: 1,
+            column,
+            kind,
+        })
+    }
+
     #[test]
     fn test_parse_point() {
         assert_eq!(parse_point("123, 321"), Ok(Point::new(123, 321)));
         assert!(parse_point("123,").is_err());
     }
 
+    #[test]
+    fn test_parse_negative() {
+        assert_eq!(parse_point("-123, 321"), Ok(Point::new(-123, 321)));
+        assert_eq!(parse_point("-1, -2"), Ok(Point::new(-1, -2)));
+        assert_eq!(parse_point("+1, -2"), Ok(Point::new(1, -2)));
+    }
+
+    #[test]
+    fn test_parse_separators() {
+        for input in &["1,2", "1, 2", "1 ,2", "1;2", "1\t2", "1   2", "  1 ,\t 2  "] {
+            assert_eq!(parse_point(input), Ok(Point::new(1, 2)), "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_parse_parentheses() {
+        assert_eq!(parse_point("(1, 2)"), Ok(Point::new(1, 2)));
+        assert_eq!(parse_point(" ( -1 ; 2 ) "), Ok(Point::new(-1, 2)));
+
+        assert_eq!(
+            parse_point("(1, 2"),
+            error_at(6, ParseErrorKind::UnclosedParenthesis)
+        );
+        assert_eq!(
+            parse_point("1, 2)"),
+            error_at(5, ParseErrorKind::TrailingCharacters)
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse_point(""), error_at(1, ParseErrorKind::ExpectedNumber));
+        assert_eq!(
+            parse_point("123,"),
+            error_at(5, ParseErrorKind::ExpectedNumber)
+        );
+        assert_eq!(
+            parse_point("12x3"),
+            error_at(3, ParseErrorKind::ExpectedSeparator)
+        );
+        assert_eq!(
+            parse_point("1, - 2"),
+            error_at(4, ParseErrorKind::ExpectedNumber)
+        );
+        assert_eq!(
+            parse_point("1, 99999999999"),
+            error_at(4, ParseErrorKind::NumberOutOfRange)
+        );
+        assert_eq!(
+            parse_point("1, 2, 3"),
+            error_at(5, ParseErrorKind::TrailingCharacters)
+        );
+    }
+
+    #[test]
+    fn test_parse_coords() {
+        assert_eq!(parse_coords("1, -2, 3"), Ok([1, -2, 3]));
+        assert_eq!(parse_coords("(1 2 3 4)"), Ok([1, 2, 3, 4]));
+        assert_eq!(parse_coords(" 7 "), Ok([7]));
+
+        assert_eq!(
+            parse_coords::<3>("1, 2"),
+            Err(ParseError::at_offset(4, ParseErrorKind::ExpectedSeparator))
+        );
+        assert_eq!(
+            parse_coords::<2>("1, 2, 3"),
+            Err(ParseError::at_offset(4, ParseErrorKind::TrailingCharacters))
+        );
+    }
+
     #[test]
     fn test_from_str() {
         assert_eq!("123, 321".parse(), Ok(Point::new(123, 321)));
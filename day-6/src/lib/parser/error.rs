@@ -0,0 +1,61 @@
+use std::{error::Error, fmt};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    ExpectedNumber,
+    NumberOutOfRange,
+    ExpectedSeparator,
+    UnclosedParenthesis,
+    TrailingCharacters,
+}
+
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let message = match self {
+            ParseErrorKind::ExpectedNumber => "expected a number",
+            ParseErrorKind::NumberOutOfRange => "number doesn't fit into i32",
+            ParseErrorKind::ExpectedSeparator => "expected ',', ';' or whitespace",
+            ParseErrorKind::UnclosedParenthesis => "expected ')'",
+            ParseErrorKind::TrailingCharacters => "unexpected characters after the point",
+        };
+
+        f.write_str(message)
+    }
+}
+
+/// Point parsing error, shared by all the parsers.
+///
+/// Both `line` and `column` are 1-based, `column` is counted in bytes;
+/// `line` is always 1 when a single point is parsed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: ParseErrorKind,
+}
+
+impl ParseError {
+    pub(crate) fn at_offset(offset: usize, kind: ParseErrorKind) -> Self {
+        Self {
+            line: 1,
+            column: offset + 1,
+            kind,
+        }
+    }
+
+    pub(crate) fn on_line(self, line: usize) -> Self {
+        Self { line, ..self }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.kind
+        )
+    }
+}
+
+impl Error for ParseError {}
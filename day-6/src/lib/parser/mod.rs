@@ -1,8 +1,109 @@
 pub mod default_parse;
 pub mod nom_parse;
 
+mod error;
 mod point;
+mod point_n;
 
-pub use self::default_parse::*;
-pub use self::nom_parse::*;
+pub use self::default_parse::{parse_coords, parse_point};
+pub use self::error::{ParseError, ParseErrorKind};
 pub use self::point::Point;
+pub use self::point_n::PointN;
+
+/// Parse one point per line with the given line parser, skipping blank lines
+pub fn parse_points_with<P, F>(input: &str, parse_line: F) -> Result<Vec<P>, ParseError>
+where
+    F: Fn(&str) -> Result<P, ParseError>,
+{
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(idx, line)| parse_line(line).map_err(|e| e.on_line(idx + 1)))
+        .collect()
+}
+
+/// Parse the whole puzzle input, one point per line
+pub fn parse_points(input: &str) -> Result<Vec<Point>, ParseError> {
+    parse_points_with(input, parse_point)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_parse_points() {
+        assert_eq!(
+            parse_points("1, 1\n\n(2;3)\r\n-4 5\n"),
+            Ok(vec![Point::new(1, 1), Point::new(2, 3), Point::new(-4, 5)])
+        );
+
+        assert_eq!(
+            parse_points("1, 1\n2, 2\n3,, 3\n"),
+            Err(ParseError {
+                line: 3,
+                column: 3,
+                kind: ParseErrorKind::ExpectedNumber,
+            })
+        );
+    }
+
+    #[test]
+    fn test_error_display() {
+        assert_eq!(
+            parse_points("1, 1\n(2, 2").unwrap_err().to_string(),
+            "line 2, column 6: expected ')'"
+        );
+    }
+
+    #[test]
+    fn test_parsers_agree_on_samples() {
+        let samples = [
+            "1, 2",
+            "1,2",
+            " (1 ; 2) ",
+            "+1\t-2",
+            "",
+            "1",
+            "1,",
+            ",1",
+            "(1, 2",
+            "1, 2)",
+            "1 2 3",
+            "--1, 2",
+            "1x2",
+            "2147483648, 0",
+            "-2147483648, 0",
+            "(, 1)",
+            "1, 2 x",
+        ];
+
+        for sample in &samples {
+            assert_eq!(
+                default_parse::parse_point(sample),
+                nom_parse::parse_point(sample),
+                "{:?}",
+                sample
+            );
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_parsers_agree(s in "[ \t(),;+x0-9-]{0,12}") {
+            prop_assert_eq!(default_parse::parse_point(&s), nom_parse::parse_point(&s));
+        }
+
+        #[test]
+        fn test_parse_roundtrip(x: i32, y: i32, separator in "[ \t]*[,;]?[ \t]+|[,;]") {
+            let point = Point::new(x, y);
+            let input = format!("{}{}{}", x, separator, y);
+
+            prop_assert_eq!(default_parse::parse_point(&input), Ok(point.clone()));
+            prop_assert_eq!(nom_parse::parse_point(&input), Ok(point));
+        }
+    }
+}
@@ -1,22 +1,68 @@
-use super::Point;
+use super::{ParseError, ParseErrorKind, Point};
 
-use advent_utils::integer_parser;
+use nom::{
+    alt, char, cond, digit1, do_parse, eof, map_res, named, one_of, opt, pair, recognize,
+    return_error, space0, space1, tuple, types::CompleteStr, Context, Err, ErrorKind,
+};
 
-use nom::{digit, map, map_res, named, separated_pair, tag, types::CompleteStr};
+const EXPECTED_NUMBER: u32 = 1;
+const EXPECTED_SEPARATOR: u32 = 2;
+const UNCLOSED_PARENTHESIS: u32 = 3;
+const TRAILING_CHARACTERS: u32 = 4;
 
-integer_parser!(parse_i32, i32);
+// a number that doesn't fit fails in `map_res!`, without a custom code
+named!(parse_i32<CompleteStr, i32>,
+    map_res!(
+        return_error!(
+            ErrorKind::Custom(EXPECTED_NUMBER),
+            recognize!(pair!(opt!(one_of!("+-")), digit1))
+        ),
+        |s: CompleteStr| s.0.parse()
+    )
+);
+
+named!(separator<CompleteStr, CompleteStr>,
+    alt!(recognize!(tuple!(space0, one_of!(",;"), space0)) | space1)
+);
 
 named!(parse_point_raw<CompleteStr, Point>,
-    map!(
-        separated_pair!(parse_i32, tag!(", "), parse_i32),
-        |(x, y)| Point::new(x, y)
+    do_parse!(
+        space0 >>
+        open: opt!(char!('(')) >>
+        space0 >>
+        x: parse_i32 >>
+        return_error!(ErrorKind::Custom(EXPECTED_SEPARATOR), separator) >>
+        y: parse_i32 >>
+        space0 >>
+        cond!(
+            open.is_some(),
+            return_error!(ErrorKind::Custom(UNCLOSED_PARENTHESIS), pair!(char!(')'), space0))
+        ) >>
+        return_error!(ErrorKind::Custom(TRAILING_CHARACTERS), eof!()) >>
+        (Point::new(x, y))
     )
 );
 
-pub fn parse_point(s: &str) -> Result<Point, nom::ErrorKind> {
+fn error_kind(kind: ErrorKind) -> ParseErrorKind {
+    match kind {
+        ErrorKind::Custom(EXPECTED_SEPARATOR) => ParseErrorKind::ExpectedSeparator,
+        ErrorKind::Custom(UNCLOSED_PARENTHESIS) => ParseErrorKind::UnclosedParenthesis,
+        ErrorKind::Custom(TRAILING_CHARACTERS) => ParseErrorKind::TrailingCharacters,
+        ErrorKind::MapRes => ParseErrorKind::NumberOutOfRange,
+        _ => ParseErrorKind::ExpectedNumber,
+    }
+}
+
+pub fn parse_point(s: &str) -> Result<Point, ParseError> {
     parse_point_raw(CompleteStr(s))
         .map(|(_, point)| point)
-        .map_err(|e| e.into_error_kind())
+        .map_err(|e| match e {
+            Err::Error(Context::Code(rest, kind)) | Err::Failure(Context::Code(rest, kind)) => {
+                ParseError::at_offset(s.len() - rest.0.len(), error_kind(kind))
+            }
+            // input is complete, so nom never asks for more
+            Err::Incomplete(_) => unreachable!(),
+        })
 }
 
 #[cfg(test)]
@@ -37,4 +83,36 @@ mod tests {
 
         assert!(parse_point("123,").is_err())
     }
+
+    #[test]
+    fn test_parse_negative() {
+        assert_eq!(parse_point("-123, 312").unwrap(), Point::new(-123, 312));
+        assert_eq!(parse_point("123, -312").unwrap(), Point::new(123, -312));
+        assert_eq!(parse_point("-1, -2").unwrap(), Point::new(-1, -2));
+
+        assert!(parse_point("--1, 2").is_err());
+        assert!(parse_point("- 1, 2").is_err());
+    }
+
+    #[test]
+    fn test_parse_flexible() {
+        assert_eq!(parse_point("1,2").unwrap(), Point::new(1, 2));
+        assert_eq!(parse_point("\t1 \t2 ").unwrap(), Point::new(1, 2));
+        assert_eq!(parse_point("(+1 ; -2)").unwrap(), Point::new(1, -2));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = parse_point("(1, 2").unwrap_err();
+        assert_eq!(
+            (error.column, error.kind),
+            (6, ParseErrorKind::UnclosedParenthesis)
+        );
+
+        let error = parse_point("1, 99999999999").unwrap_err();
+        assert_eq!(
+            (error.column, error.kind),
+            (4, ParseErrorKind::NumberOutOfRange)
+        );
+    }
 }
@@ -0,0 +1,72 @@
+use std::str::FromStr;
+
+use super::{default_parse::parse_coords, ParseError, Point};
+
+/// Point with `N` integer coordinates
+#[derive(Debug, PartialEq, PartialOrd, Eq, Hash, Clone)]
+pub struct PointN<const N: usize> {
+    pub coords: [i32; N],
+}
+
+impl<const N: usize> PointN<N> {
+    pub fn new(coords: [i32; N]) -> Self {
+        Self { coords }
+    }
+
+    /// Manhattan distance, computed in `i64` so that it can't overflow for any `N` in use
+    pub fn distance_to(&self, other: &Self) -> i64 {
+        self.coords
+            .iter()
+            .zip(&other.coords)
+            .map(|(a, b)| (i64::from(*a) - i64::from(*b)).abs())
+            .sum()
+    }
+}
+
+impl From<Point> for PointN<2> {
+    fn from(point: Point) -> Self {
+        Self::new([point.x, point.y])
+    }
+}
+
+impl<const N: usize> FromStr for PointN<N> {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_coords(s).map(Self::new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("1, 2, 3".parse(), Ok(PointN::new([1, 2, 3])));
+        assert_eq!("(-1;0;5;8)".parse(), Ok(PointN::new([-1, 0, 5, 8])));
+        assert!("1, 2".parse::<PointN<3>>().is_err());
+    }
+
+    #[test]
+    fn test_distance_to() {
+        let p = PointN::new([1, -2, 3]);
+
+        assert_eq!(p.distance_to(&p), 0);
+        assert_eq!(p.distance_to(&PointN::new([0, 0, 0])), 6);
+        assert_eq!(
+            PointN::new([i32::MIN, i32::MAX]).distance_to(&PointN::new([i32::MAX, i32::MIN])),
+            2 * (i64::from(i32::MAX) - i64::from(i32::MIN))
+        );
+    }
+
+    #[test]
+    fn test_from_point() {
+        let p = Point::new(3, 3);
+
+        assert_eq!(
+            PointN::from(p.clone()).distance_to(&PointN::new([0, 0])),
+            i64::from(p.distance_to(0, 0))
+        );
+    }
+}
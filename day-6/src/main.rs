@@ -1,29 +1,127 @@
-use std::error::Error;
+use std::{error::Error, fs::File, io::BufWriter};
 
 use serde::Deserialize;
 
-use advent_utils::{get_custom_config, parse_file, Part};
+use advent_utils::{get_custom_config, read_file, Part};
 
-use day_6_parser::Point;
+use day_6_parser::{parse_points, parse_points_with, Point, PointN};
 
 mod world;
 
-use crate::world::World;
+use crate::world::{safe_region_size, Chebyshev, Euclidean, Metric, World};
+
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum MetricKind {
+    #[default]
+    Manhattan,
+    Chebyshev,
+    Euclidean,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum RenderFormat {
+    Letters,
+    Ppm,
+    Png,
+}
 
 #[derive(Debug, Deserialize)]
 struct Config {
     part: Part,
     input_file: String,
     max_distance: Option<i32>,
+    #[serde(default)]
+    metric: MetricKind,
+    render: Option<RenderFormat>,
+    render_file: Option<String>,
+    #[serde(default)]
+    render_margin: u32,
+    dimensions: Option<usize>,
+    #[serde(default)]
+    report: bool,
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
     let config = get_custom_config::<Config>()?;
-    let points: Vec<Point> = parse_file(&config.input_file)?;
-    let world = World::from_points(points);
+    let input = read_file(&config.input_file)?;
+
+    match config.dimensions {
+        None | Some(2) => {}
+        Some(3) => return solve_3d(&config, &input),
+        Some(n) => return Err(format!("{}-dimensional points are not supported", n).into()),
+    }
+
+    let points = parse_points(&input)?;
+
+    match config.metric {
+        MetricKind::Manhattan => solve(&config, World::from_points(points)),
+        MetricKind::Chebyshev => solve(&config, World::with_metric(points, Chebyshev)),
+        MetricKind::Euclidean => solve(&config, World::with_metric(points, Euclidean)),
+    }
+}
+
+fn solve_3d(config: &Config, input: &str) -> Result<(), Box<dyn Error>> {
+    if let Part::One = config.part {
+        return Err("Only safe region size can be computed in 3-D".into());
+    }
+
+    let points = parse_points_with(input, str::parse::<PointN<3>>)?;
+    let max_distance = config
+        .max_distance
+        .expect("Max distance should be specified for part two");
+
+    println!(
+        "Safe region has size of {}",
+        safe_region_size(&points, max_distance)
+    );
+
+    Ok(())
+}
+
+fn render<M: Metric>(config: &Config, world: &World<M>) -> Result<(), Box<dyn Error>> {
+    let format = match config.render {
+        Some(format) => format,
+        None => return Ok(()),
+    };
+
+    if let RenderFormat::Letters = format {
+        print!("{}", world.render_letters(config.render_margin)?);
+
+        return Ok(());
+    }
+
+    let path = config
+        .render_file
+        .as_ref()
+        .expect("Render file should be specified for image output");
+    let file = BufWriter::new(File::create(path)?);
+    let image = world.render_image(config.render_margin, config.max_distance);
+
+    match format {
+        RenderFormat::Ppm => image.write_ppm(file),
+        _ => image.write_png(file),
+    }
+}
+
+fn solve<M: Metric>(config: &Config, world: World<M>) -> Result<(), Box<dyn Error>> {
+    render(config, &world)?;
 
     match config.part {
         Part::One => {
+            if config.report {
+                let report = world.area_report();
+                print!("{}", report);
+
+                if let Some(second) = report.nth_largest_finite(1) {
+                    println!(
+                        "Second largest closed area of size {} belongs to ({}, {})",
+                        second.area, second.point.x, second.point.y
+                    );
+                }
+            }
+
             println!(
                 "Largest closed area has size of {}",
                 world.largest_finite_area().unwrap()
@@ -1,44 +1,103 @@
+use super::Point;
+
+/// Bounding box of the world, both bounds are inclusive.
+///
+/// Box with `min > max` on any axis is empty (that's the state before any point is included).
 #[derive(Debug)]
-pub(super) struct Boundaries {
-    top: i32,
-    right: i32,
-    bottom: i32,
-    left: i32,
+pub(crate) struct Boundaries {
+    min_x: i32,
+    max_x: i32,
+    min_y: i32,
+    max_y: i32,
 }
 
 impl Boundaries {
     pub(super) fn new() -> Self {
         Self {
-            top: i32::min_value(),
-            right: i32::min_value(),
-            bottom: i32::max_value(),
-            left: i32::max_value(),
+            min_x: i32::MAX,
+            max_x: i32::MIN,
+            min_y: i32::MAX,
+            max_y: i32::MIN,
+        }
+    }
+
+    /// Smallest box containing all of the `points`
+    pub(super) fn from_points<'a>(points: impl IntoIterator<Item = &'a Point>) -> Self {
+        let mut boundaries = Self::new();
+        for point in points {
+            boundaries.include_point(point.x, point.y);
+        }
+
+        boundaries
+    }
+
+    pub(super) fn is_empty(&self) -> bool {
+        self.min_x > self.max_x || self.min_y > self.max_y
+    }
+
+    /// Top-left (minimal) corner of the box
+    pub(super) fn origin(&self) -> (i32, i32) {
+        (self.min_x, self.min_y)
+    }
+
+    pub(super) fn width(&self) -> usize {
+        if self.is_empty() {
+            0
+        } else {
+            (i64::from(self.max_x) - i64::from(self.min_x) + 1) as usize
+        }
+    }
+
+    pub(super) fn height(&self) -> usize {
+        if self.is_empty() {
+            0
+        } else {
+            (i64::from(self.max_y) - i64::from(self.min_y) + 1) as usize
+        }
+    }
+
+    /// Box grown by `margin` cells in every direction
+    pub(super) fn expanded(&self, margin: i32) -> Self {
+        self.expanded_by(margin, margin)
+    }
+
+    /// Grows the box by `margin_x` to the left and right, and by `margin_y` up and down
+    pub(super) fn expanded_by(&self, margin_x: i32, margin_y: i32) -> Self {
+        if self.is_empty() {
+            return Self::new();
+        }
+
+        Self {
+            min_x: self.min_x.saturating_sub(margin_x),
+            max_x: self.max_x.saturating_add(margin_x),
+            min_y: self.min_y.saturating_sub(margin_y),
+            max_y: self.max_y.saturating_add(margin_y),
         }
     }
 
     pub(super) fn include_point(&mut self, x: i32, y: i32) {
-        if x < self.left {
-            self.left = x;
+        if x < self.min_x {
+            self.min_x = x;
         }
-        if x > self.right {
-            self.right = x;
+        if x > self.max_x {
+            self.max_x = x;
         }
 
-        if y < self.bottom {
-            self.bottom = y;
+        if y < self.min_y {
+            self.min_y = y;
         }
-        if y > self.top {
-            self.top = y;
+        if y > self.max_y {
+            self.max_y = y;
         }
     }
 
     pub(super) fn is_inner_point(&self, x: i32, y: i32) -> bool {
-        x >= self.left && x <= self.right && y >= self.bottom && x <= self.top
+        x >= self.min_x && x <= self.max_x && y >= self.min_y && y <= self.max_y
     }
 
     pub(super) fn is_edge_point(&self, x: i32, y: i32) -> bool {
         self.is_inner_point(x, y)
-            && (self.left == x || self.right == x || self.top == y || self.bottom == y)
+            && (self.min_x == x || self.max_x == x || self.min_y == y || self.max_y == y)
     }
 }
 
@@ -47,38 +106,41 @@ impl<'a> IntoIterator for &'a Boundaries {
     type IntoIter = BoundariesIterator<'a>;
 
     fn into_iter(self) -> Self::IntoIter {
+        let next = if self.is_empty() {
+            None
+        } else {
+            Some((self.min_x, self.min_y))
+        };
+
         BoundariesIterator {
-            boundaries: &self,
-            current_x: self.left - 1, // to start from first point
-            current_y: self.bottom,
+            boundaries: self,
+            next,
         }
     }
 }
 
+/// Row-by-row iterator over all points of the box, from `min_y` to `max_y`.
 pub struct BoundariesIterator<'a> {
     boundaries: &'a Boundaries,
-    current_x: i32,
-    current_y: i32,
+    next: Option<(i32, i32)>,
 }
 
 impl Iterator for BoundariesIterator<'_> {
     type Item = (i32, i32);
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.current_x += 1;
-
-        if self.current_x <= self.boundaries.right {
-            return Some((self.current_x, self.current_y));
-        }
-
-        self.current_x = self.boundaries.left;
-        self.current_y += 1;
+        let current = self.next?;
+        let (x, y) = current;
 
-        if self.current_y <= self.boundaries.top {
-            Some((self.current_x, self.current_y))
+        self.next = if x < self.boundaries.max_x {
+            Some((x + 1, y))
+        } else if y < self.boundaries.max_y {
+            Some((self.boundaries.min_x, y + 1))
         } else {
             None
-        }
+        };
+
+        Some(current)
     }
 }
 
@@ -89,10 +151,12 @@ mod tests {
     #[test]
     fn test_boundaries() {
         let mut b = Boundaries::new();
+        assert!(b.is_empty());
         assert!(!b.is_inner_point(0, 0));
         assert!(!b.is_edge_point(0, 0));
 
         b.include_point(0, 0);
+        assert!(!b.is_empty());
         assert!(b.is_inner_point(0, 0));
         assert!(b.is_edge_point(0, 0));
 
@@ -116,6 +180,69 @@ mod tests {
         assert!(b.is_inner_point(5, 5));
     }
 
+    #[test]
+    fn test_boundaries_non_square() {
+        let mut b = Boundaries::new();
+        b.include_point(0, 0);
+        b.include_point(2, 10);
+
+        assert!(b.is_inner_point(1, 7));
+        assert!(!b.is_edge_point(1, 7));
+        assert!(b.is_edge_point(1, 10));
+        assert!(b.is_edge_point(2, 7));
+
+        // y is outside, even though it's within the x range
+        assert!(!b.is_inner_point(1, 11));
+        assert!(!b.is_edge_point(1, 11));
+        assert!(!b.is_inner_point(1, -1));
+        assert!(!b.is_inner_point(3, 5));
+    }
+
+    #[test]
+    fn test_boundaries_negative() {
+        let mut b = Boundaries::new();
+        b.include_point(-5, -3);
+        b.include_point(-1, 2);
+
+        assert!(b.is_inner_point(-3, 0));
+        assert!(b.is_edge_point(-5, 0));
+        assert!(b.is_edge_point(-3, -3));
+        assert!(!b.is_edge_point(-3, -1));
+        assert!(!b.is_inner_point(0, 0));
+
+        assert_eq!(b.origin(), (-5, -3));
+        assert_eq!((b.width(), b.height()), (5, 6));
+        assert_eq!(b.into_iter().count(), 5 * 6);
+    }
+
+    #[test]
+    fn test_boundaries_expanded() {
+        let mut b = Boundaries::new();
+        assert!(b.expanded(3).is_empty());
+
+        b.include_point(1, 2);
+        b.include_point(4, 3);
+
+        let e = b.expanded(2);
+        assert_eq!(e.origin(), (-1, 0));
+        assert_eq!((e.width(), e.height()), (8, 6));
+        assert!(e.is_edge_point(6, 2));
+        assert!(!e.is_edge_point(4, 3));
+
+        let e = b.expanded_by(1, 3);
+        assert_eq!(e.origin(), (0, -1));
+        assert_eq!((e.width(), e.height()), (6, 8));
+    }
+
+    #[test]
+    fn test_boundaries_from_points() {
+        let b = Boundaries::from_points(&[Point::new(3, -1), Point::new(-2, 4), Point::new(0, 0)]);
+
+        assert_eq!(b.origin(), (-2, -1));
+        assert_eq!((b.width(), b.height()), (6, 6));
+        assert!(Boundaries::from_points(&[]).is_empty());
+    }
+
     #[test]
     fn test_boundaries_iteration() {
         let mut b = Boundaries::new();
@@ -125,12 +252,31 @@ mod tests {
 
         let mut should_iterate_over = Vec::new();
 
-        for y in b.bottom..=b.top {
-            for x in b.left..=b.right {
+        for y in b.min_y..=b.max_y {
+            for x in b.min_x..=b.max_x {
                 should_iterate_over.push((x, y));
             }
         }
 
         assert_eq!(b.into_iter().collect::<Vec<_>>(), should_iterate_over);
     }
+
+    #[test]
+    fn test_boundaries_iteration_edge_cases() {
+        let b = Boundaries::new();
+        assert_eq!((b.width(), b.height()), (0, 0));
+        assert_eq!(b.into_iter().next(), None);
+
+        let mut b = Boundaries::new();
+        b.include_point(7, -7);
+        assert_eq!(b.into_iter().collect::<Vec<_>>(), vec![(7, -7)]);
+
+        let mut b = Boundaries::new();
+        b.include_point(i32::MAX, i32::MIN);
+        b.include_point(i32::MAX - 1, i32::MIN);
+        assert_eq!(
+            b.into_iter().collect::<Vec<_>>(),
+            vec![(i32::MAX - 1, i32::MIN), (i32::MAX, i32::MIN),]
+        );
+    }
 }
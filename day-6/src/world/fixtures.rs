@@ -0,0 +1,12 @@
+use super::{Point, World};
+
+/// Points from the puzzle text: https://adventofcode.com/2018/day/6
+pub(super) const EXAMPLE: [(i32, i32); 6] = [(1, 1), (1, 6), (8, 3), (3, 4), (5, 5), (8, 9)];
+
+pub(super) fn world_from(points: &[(i32, i32)]) -> World {
+    World::from_points(points.iter().map(|(x, y)| Point::new(*x, *y)).collect())
+}
+
+pub(super) fn example_world() -> World {
+    world_from(&EXAMPLE)
+}
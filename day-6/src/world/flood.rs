@@ -0,0 +1,214 @@
+use super::{boundaries::Boundaries, Point};
+
+// Cell state is packed into `u32`: index of the owning point, or one of the markers below.
+// Cells reached on the level being expanded are additionally flagged as tentative,
+// so ties can be detected without storing the distance for every cell.
+const TENTATIVE: u32 = 1 << 31;
+const UNVISITED: u32 = TENTATIVE - 1;
+const TIE: u32 = TENTATIVE - 2;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum Owner {
+    Point(usize),
+    Tie,
+}
+
+/// Moves between neighbouring cells, for which BFS distance is the Manhattan distance
+pub(super) const ROOK_MOVES: [(i32, i32); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
+/// Moves between neighbouring cells, for which BFS distance is the Chebyshev distance
+pub(super) const KING_MOVES: [(i32, i32); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+/// Nearest-point map of the bounding box, built by multi-source BFS.
+///
+/// Inside of the box BFS distance over the `moves` grid is exactly the matching metric
+/// (Manhattan for rook moves, Chebyshev for king moves), and a cell's set of nearest points
+/// is the union of its parents' sets, so ownership propagates level by level.
+#[derive(Debug)]
+pub(super) struct Flood {
+    origin: (i32, i32),
+    width: usize,
+    height: usize,
+    cells: Vec<u32>,
+}
+
+impl Flood {
+    pub(super) fn new(boundaries: &Boundaries, points: &[Point], moves: &[(i32, i32)]) -> Self {
+        assert!(
+            points.len() < TIE as usize,
+            "too many points for flood fill"
+        );
+
+        let (width, height) = (boundaries.width(), boundaries.height());
+        let (min_x, min_y) = boundaries.origin();
+        let mut cells = vec![UNVISITED; width * height];
+
+        let mut frontier = Vec::with_capacity(points.len());
+        for (idx, point) in points.iter().enumerate() {
+            let cell = (point.y - min_y) as usize * width + (point.x - min_x) as usize;
+
+            cells[cell] = if cells[cell] == UNVISITED {
+                frontier.push(cell);
+                idx as u32
+            } else {
+                TIE
+            };
+        }
+
+        let mut next = Vec::new();
+
+        while !frontier.is_empty() {
+            for &cell in &frontier {
+                let owner = cells[cell];
+                let (col, row) = ((cell % width) as i64, (cell / width) as i64);
+
+                let neighbours = moves.iter().filter_map(|(dx, dy)| {
+                    let (col, row) = (col + i64::from(*dx), row + i64::from(*dy));
+
+                    if col >= 0 && row >= 0 && col < width as i64 && row < height as i64 {
+                        Some(row as usize * width + col as usize)
+                    } else {
+                        None
+                    }
+                });
+
+                for neighbour in neighbours {
+                    let state = cells[neighbour];
+
+                    if state == UNVISITED {
+                        cells[neighbour] = owner | TENTATIVE;
+                        next.push(neighbour);
+                    } else if state & TENTATIVE != 0 && state != owner | TENTATIVE {
+                        cells[neighbour] = TIE | TENTATIVE;
+                    }
+                }
+            }
+
+            for &cell in &next {
+                cells[cell] &= !TENTATIVE;
+            }
+
+            frontier.clear();
+            std::mem::swap(&mut frontier, &mut next);
+        }
+
+        Self {
+            origin: (min_x, min_y),
+            width,
+            height,
+            cells,
+        }
+    }
+
+    /// Owner of the cell, which must lie inside of the box
+    pub(super) fn owner(&self, x: i32, y: i32) -> Owner {
+        let (col, row) = ((x - self.origin.0) as usize, (y - self.origin.1) as usize);
+        debug_assert!(col < self.width && row < self.height);
+
+        match self.cells[row * self.width + col] {
+            TIE => Owner::Tie,
+            idx => Owner::Point(idx as usize),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flood_of(points: &[Point]) -> Flood {
+        Flood::new(&Boundaries::from_points(points), points, &ROOK_MOVES)
+    }
+
+    #[test]
+    fn test_flood_owners() {
+        let points = vec![Point::new(0, 0), Point::new(4, 0)];
+        let flood = flood_of(&points);
+
+        let owners = (0..5).map(|x| flood.owner(x, 0)).collect::<Vec<_>>();
+
+        assert_eq!(
+            owners,
+            vec![
+                Owner::Point(0),
+                Owner::Point(0),
+                Owner::Tie,
+                Owner::Point(1),
+                Owner::Point(1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_flood_tie_propagates() {
+        // (1, 0) is a tie, so (1, 1) can't belong to anybody either
+        let points = vec![Point::new(0, 0), Point::new(2, 0), Point::new(1, 3)];
+        let flood = flood_of(&points);
+
+        assert_eq!(flood.owner(1, 0), Owner::Tie);
+        assert_eq!(flood.owner(1, 1), Owner::Tie);
+        assert_eq!(flood.owner(1, 2), Owner::Point(2));
+    }
+
+    #[test]
+    fn test_flood_matches_brute_force() {
+        let points = vec![
+            Point::new(1, 1),
+            Point::new(1, 6),
+            Point::new(8, 3),
+            Point::new(3, 4),
+            Point::new(5, 5),
+            Point::new(8, 9),
+        ];
+        let flood = flood_of(&points);
+
+        for y in 1..=9 {
+            for x in 1..=8 {
+                let min = points.iter().map(|p| p.distance_to(x, y)).min().unwrap();
+                let closest = points
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, p)| p.distance_to(x, y) == min)
+                    .map(|(idx, _)| idx)
+                    .collect::<Vec<_>>();
+
+                let expected = match closest.as_slice() {
+                    [idx] => Owner::Point(*idx),
+                    _ => Owner::Tie,
+                };
+
+                assert_eq!(flood.owner(x, y), expected, "cell {}, {}", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn test_flood_king_moves() {
+        let points = vec![Point::new(0, 0), Point::new(4, 2)];
+        let flood = Flood::new(&Boundaries::from_points(&points), &points, &KING_MOVES);
+
+        // Chebyshev distances: (2, 2) is 2 away from both, (1, 2) is 2 and 3 away
+        assert_eq!(flood.owner(2, 2), Owner::Tie);
+        assert_eq!(flood.owner(1, 2), Owner::Point(0));
+        assert_eq!(flood.owner(3, 0), Owner::Point(1));
+        assert_eq!(flood.owner(2, 0), Owner::Tie);
+    }
+
+    #[test]
+    fn test_flood_negative() {
+        let points = vec![Point::new(-3, -1), Point::new(-1, -1)];
+        let flood = flood_of(&points);
+
+        assert_eq!(flood.owner(-3, -1), Owner::Point(0));
+        assert_eq!(flood.owner(-2, -1), Owner::Tie);
+        assert_eq!(flood.owner(-1, -1), Owner::Point(1));
+    }
+}
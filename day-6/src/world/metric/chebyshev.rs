@@ -0,0 +1,63 @@
+use std::cmp::max;
+
+use super::{flood_areas, Boundaries, Metric, Point};
+
+use crate::world::flood::KING_MOVES;
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Chebyshev;
+
+impl Metric for Chebyshev {
+    fn distance(&self, point: &Point, x: i32, y: i32) -> i64 {
+        i64::from(max((point.x - x).abs(), (point.y - y).abs()))
+    }
+
+    fn flood_moves(&self) -> Option<&'static [(i32, i32)]> {
+        Some(&KING_MOVES)
+    }
+
+    fn areas(&self, points: &[Point], boundaries: &Boundaries) -> Vec<Option<i32>> {
+        // Unlike Manhattan, owning a cell on the box edge doesn't make an area infinite,
+        // so the box is grown until it does. A cell level with the box (beside it on the left or
+        // right, or above or below it) is at least as far from every point along the
+        // outward axis as across it once the box is grown by its extent across that axis,
+        // so stepping straight outwards increases distance to every point by one.
+        // Any other cell is diagonally past a corner of the box, where stepping diagonally
+        // outwards does the same. Either way, the owner of a cell on the grown edge also owns
+        // the whole ray beyond it; and an infinite area always reaches the edge, since every
+        // step towards its point along a shortest path stays within it.
+        let margin_x = boundaries.height().saturating_sub(1) as i32;
+        let margin_y = boundaries.width().saturating_sub(1) as i32;
+
+        flood_areas(
+            points,
+            &boundaries.expanded_by(margin_x, margin_y),
+            &KING_MOVES,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distance() {
+        let p = Point::new(3, 3);
+
+        assert_eq!(Chebyshev.distance(&p, 0, 0), 3);
+        assert_eq!(Chebyshev.distance(&p, 5, -1), 4);
+    }
+
+    #[test]
+    fn test_edge_area_can_be_finite() {
+        // middle point owns the box (which is a single row), but only a few cells beyond it
+        let points = vec![Point::new(0, 0), Point::new(4, 0), Point::new(2, 0)];
+        let boundaries = Boundaries::from_points(&points);
+
+        assert_eq!(
+            Chebyshev.areas(&points, &boundaries),
+            vec![None, None, Some(3)]
+        );
+    }
+}
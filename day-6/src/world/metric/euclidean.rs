@@ -0,0 +1,213 @@
+use super::{Boundaries, Metric, Point};
+
+/// Squared Euclidean distance, which gives the same areas while staying in integers
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Euclidean;
+
+fn cross(o: &Point, a: &Point, b: &Point) -> i64 {
+    i64::from(a.x - o.x) * i64::from(b.y - o.y) - i64::from(a.y - o.y) * i64::from(b.x - o.x)
+}
+
+/// Whether every point lies on the boundary of the convex hull.
+///
+/// Those are exactly the points with unbounded areas: hull vertices own a cone,
+/// and points inside of hull edges own a half-strip, which always contains lattice points.
+fn on_hull(points: &[Point]) -> Vec<bool> {
+    let mut sorted = points.iter().collect::<Vec<_>>();
+    sorted.sort_by_key(|p| (p.x, p.y));
+
+    // Andrew's monotone chain, keeping only the strict vertices
+    let mut hull: Vec<&Point> = Vec::with_capacity(2 * sorted.len());
+    for pass in 0..2 {
+        let start = hull.len();
+
+        for point in &sorted {
+            while hull.len() >= start + 2
+                && cross(hull[hull.len() - 2], hull[hull.len() - 1], point) <= 0
+            {
+                hull.pop();
+            }
+            hull.push(point);
+        }
+
+        hull.pop();
+        if pass == 0 {
+            sorted.reverse();
+        }
+    }
+
+    if hull.len() < 3 {
+        // all points are collinear
+        return vec![true; points.len()];
+    }
+
+    points
+        .iter()
+        .map(|p| {
+            hull.iter().zip(hull.iter().cycle().skip(1)).any(|(a, b)| {
+                cross(a, b, p) == 0
+                    && p.x >= a.x.min(b.x)
+                    && p.x <= a.x.max(b.x)
+                    && p.y >= a.y.min(b.y)
+                    && p.y <= a.y.max(b.y)
+            })
+        })
+        .collect()
+}
+
+/// Real interval `(lo, hi)` of x, where cells of row `y` are strictly closer to `points[idx]`
+/// than to any other point; bounds are fractions `(numerator, positive denominator)`
+fn row_interval(points: &[Point], idx: usize, y: i32) -> Option<((i64, i64), (i64, i64))> {
+    let p = &points[idx];
+    let (px, py) = (i64::from(p.x), i64::from(p.y));
+    let y = i64::from(y);
+
+    let mut lo: Option<(i64, i64)> = None;
+    let mut hi: Option<(i64, i64)> = None;
+
+    for (_, q) in points.iter().enumerate().filter(|(other, _)| *other != idx) {
+        let (qx, qy) = (i64::from(q.x), i64::from(q.y));
+
+        // |c - p|^2 < |c - q|^2  <=>  a * x < b
+        let a = 2 * (qx - px);
+        let b = qx * qx + qy * qy - px * px - py * py - 2 * y * (qy - py);
+
+        if a > 0 {
+            if hi.is_none_or(|(n, d)| i128::from(b) * i128::from(d) < i128::from(n) * i128::from(a))
+            {
+                hi = Some((b, a));
+            }
+        } else if a < 0 {
+            let (n, d) = (-b, -a);
+
+            if lo.is_none_or(|(ln, ld)| {
+                i128::from(n) * i128::from(ld) > i128::from(ln) * i128::from(d)
+            }) {
+                lo = Some((n, d));
+            }
+        } else if b <= 0 {
+            return None;
+        }
+    }
+
+    // points not on the hull have neighbours on both sides of every row
+    let (lo, hi) = (lo?, hi?);
+
+    if i128::from(lo.0) * i128::from(hi.1) < i128::from(hi.0) * i128::from(lo.1) {
+        Some((lo, hi))
+    } else {
+        None
+    }
+}
+
+/// Number of integers strictly inside of the interval
+fn integers_between((lo, hi): ((i64, i64), (i64, i64))) -> i32 {
+    let first = lo.0.div_euclid(lo.1) + 1;
+    let last = -((-hi.0).div_euclid(hi.1)) - 1;
+
+    (last - first + 1).max(0) as i32
+}
+
+/// Size of the bounded area of a point, scanning rows outwards while the area is non-empty
+fn bounded_area(points: &[Point], idx: usize) -> i32 {
+    let start = points[idx].y;
+
+    let up = (start..=i32::MAX)
+        .map_while(|y| row_interval(points, idx, y))
+        .map(integers_between)
+        .sum::<i32>();
+    let down = (i32::MIN..start)
+        .rev()
+        .map_while(|y| row_interval(points, idx, y))
+        .map(integers_between)
+        .sum::<i32>();
+
+    up + down
+}
+
+impl Metric for Euclidean {
+    fn distance(&self, point: &Point, x: i32, y: i32) -> i64 {
+        let (dx, dy) = (i64::from(point.x - x), i64::from(point.y - y));
+
+        dx * dx + dy * dy
+    }
+
+    fn areas(&self, points: &[Point], _boundaries: &Boundaries) -> Vec<Option<i32>> {
+        // finite areas may reach far outside of the bounding box,
+        // so they're counted from exact row intervals instead of a grid
+        on_hull(points)
+            .into_iter()
+            .enumerate()
+            .map(|(idx, on_hull)| {
+                if on_hull {
+                    None
+                } else {
+                    Some(bounded_area(points, idx))
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn points(coords: &[(i32, i32)]) -> Vec<Point> {
+        coords.iter().map(|(x, y)| Point::new(*x, *y)).collect()
+    }
+
+    #[test]
+    fn test_distance() {
+        let p = Point::new(3, 3);
+
+        assert_eq!(Euclidean.distance(&p, 0, 0), 18);
+        assert_eq!(Euclidean.distance(&p, 5, -1), 20);
+    }
+
+    #[test]
+    fn test_on_hull() {
+        let square = points(&[(0, 0), (4, 0), (0, 4), (4, 4), (2, 0), (2, 2), (1, 3)]);
+        assert_eq!(
+            on_hull(&square),
+            vec![true, true, true, true, true, false, false]
+        );
+
+        let line = points(&[(0, 0), (1, 1), (5, 5)]);
+        assert_eq!(on_hull(&line), vec![true, true, true]);
+
+        assert_eq!(on_hull(&points(&[(3, 3)])), vec![true]);
+    }
+
+    #[test]
+    fn test_integers_between() {
+        assert_eq!(integers_between(((0, 1), (3, 1))), 2);
+        assert_eq!(integers_between(((-1, 2), (1, 2))), 1);
+        assert_eq!(integers_between(((1, 3), (2, 3))), 0);
+        assert_eq!(integers_between(((-7, 2), (-5, 2))), 1);
+        assert_eq!(integers_between(((-7, 2), (-3, 2))), 2);
+        assert_eq!(integers_between(((-6, 2), (-2, 2))), 1);
+    }
+
+    #[test]
+    fn test_center_area() {
+        // center owns itself and its 4 neighbours, diagonal ones tie with the corners
+        let world = points(&[(0, 0), (4, 0), (0, 4), (4, 4), (2, 2)]);
+        let boundaries = Boundaries::from_points(&world);
+
+        assert_eq!(
+            Euclidean.areas(&world, &boundaries),
+            vec![None, None, None, None, Some(5)]
+        );
+    }
+
+    #[test]
+    fn test_area_reaching_outside_of_the_box() {
+        // flat triangle: area of the inner point reaches y = -199, far below the box
+        let world = points(&[(0, 0), (40, 0), (20, 2), (20, 1)]);
+
+        assert_eq!(on_hull(&world), vec![true, true, true, false]);
+        assert_eq!(row_interval(&world, 3, -199).map(integers_between), Some(1));
+        assert_eq!(row_interval(&world, 3, -200), None);
+    }
+}
@@ -0,0 +1,61 @@
+use super::{flood_areas, safe_margin, Boundaries, Metric, Point};
+
+use crate::world::{
+    flood::ROOK_MOVES,
+    separable::{axis_distance_sums, count_below},
+};
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Manhattan;
+
+impl Metric for Manhattan {
+    fn distance(&self, point: &Point, x: i32, y: i32) -> i64 {
+        i64::from(point.distance_to(x, y))
+    }
+
+    fn flood_moves(&self) -> Option<&'static [(i32, i32)]> {
+        Some(&ROOK_MOVES)
+    }
+
+    fn areas(&self, points: &[Point], boundaries: &Boundaries) -> Vec<Option<i32>> {
+        // moving away from the box increases distance to every point by one,
+        // so areas owning a cell on the box edge are exactly the infinite ones
+        flood_areas(points, boundaries, &ROOK_MOVES)
+    }
+
+    fn safe_area_size(
+        &self,
+        points: &[Point],
+        boundaries: &Boundaries,
+        max_distance: i32,
+    ) -> usize {
+        if points.is_empty() {
+            return 0;
+        }
+
+        // total distance is a sum of independent x and y parts, so it's enough
+        // to compute both parts once per column and row, and pair them up
+        let window = boundaries.expanded(safe_margin(points.len(), max_distance));
+        let (min_x, min_y) = window.origin();
+
+        let mut axes = [
+            axis_distance_sums(points.iter().map(|p| p.x), min_x, window.width()),
+            axis_distance_sums(points.iter().map(|p| p.y), min_y, window.height()),
+        ];
+
+        count_below(&mut axes, i64::from(max_distance))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_distance() {
+        let p = Point::new(3, 3);
+
+        assert_eq!(Manhattan.distance(&p, 0, 0), 6);
+        assert_eq!(Manhattan.distance(&p, 5, -1), 6);
+    }
+}
@@ -0,0 +1,229 @@
+use super::{
+    boundaries::Boundaries,
+    flood::{Flood, Owner},
+    Point,
+};
+
+mod chebyshev;
+mod euclidean;
+mod manhattan;
+
+pub use self::chebyshev::Chebyshev;
+pub use self::euclidean::Euclidean;
+pub use self::manhattan::Manhattan;
+
+pub trait Metric {
+    fn distance(&self, point: &Point, x: i32, y: i32) -> i64;
+
+    /// Moves, for which BFS distance over the grid is this metric, if there are such
+    fn flood_moves(&self) -> Option<&'static [(i32, i32)]> {
+        None
+    }
+
+    /// Area size of every point, `None` for infinite areas
+    fn areas(&self, points: &[Point], boundaries: &Boundaries) -> Vec<Option<i32>>;
+
+    /// Number of cells with total distance to all points less than `max_distance`
+    fn safe_area_size(
+        &self,
+        points: &[Point],
+        boundaries: &Boundaries,
+        max_distance: i32,
+    ) -> usize {
+        if points.is_empty() {
+            return 0;
+        }
+
+        boundaries
+            .expanded(safe_margin(points.len(), max_distance))
+            .into_iter()
+            .filter(|(x, y)| {
+                points
+                    .iter()
+                    .map(|point| self.distance(point, *x, *y))
+                    .sum::<i64>()
+                    < i64::from(max_distance)
+            })
+            .count()
+    }
+}
+
+/// How far outside of the box the safe region can reach.
+///
+/// A cell `m` steps away from the box is at least `m` away from every one of `n` points
+/// (for all metrics here), so it is safe only if `n * m < max_distance`.
+pub(super) fn safe_margin(n_points: usize, max_distance: i32) -> i32 {
+    if max_distance <= 0 {
+        0
+    } else {
+        (i64::from(max_distance) / n_points as i64) as i32
+    }
+}
+
+/// Count areas inside of `boundaries` with BFS, areas touching the edge are infinite
+fn flood_areas(
+    points: &[Point],
+    boundaries: &Boundaries,
+    moves: &[(i32, i32)],
+) -> Vec<Option<i32>> {
+    let flood = Flood::new(boundaries, points, moves);
+    let mut areas = vec![Some(0); points.len()];
+
+    for (x, y) in boundaries {
+        if let Owner::Point(idx) = flood.owner(x, y) {
+            areas[idx] = if boundaries.is_edge_point(x, y) {
+                None
+            } else {
+                areas[idx].map(|area| area + 1)
+            };
+        }
+    }
+
+    areas
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use proptest::prelude::*;
+
+    /// Owners of all cells in the box grown by `margin`, counted by brute force;
+    /// areas owning a cell on the outer edge are infinite
+    fn brute_force_areas(metric: &impl Metric, points: &[Point], margin: i32) -> Vec<Option<i32>> {
+        let window = Boundaries::from_points(points).expanded(margin);
+
+        let mut areas = vec![Some(0); points.len()];
+
+        for (x, y) in &window {
+            let distances = points
+                .iter()
+                .map(|point| metric.distance(point, x, y))
+                .collect::<Vec<_>>();
+            let min = *distances.iter().min().unwrap();
+            let mut closest = distances.iter().enumerate().filter(|(_, d)| **d == min);
+
+            if let (Some((idx, _)), None) = (closest.next(), closest.next()) {
+                areas[idx] = if window.is_edge_point(x, y) {
+                    None
+                } else {
+                    areas[idx].map(|area| area + 1)
+                };
+            }
+        }
+
+        areas
+    }
+
+    fn areas_of(metric: &impl Metric, points: &[Point]) -> Vec<Option<i32>> {
+        metric.areas(points, &Boundaries::from_points(points))
+    }
+
+    fn points_strategy(range: i32, max_len: usize) -> impl Strategy<Value = Vec<Point>> {
+        prop::collection::hash_set((-range..range, -range..range), 1..max_len).prop_map(|set| {
+            set.into_iter()
+                .map(|(x, y)| Point::new(x, y))
+                .collect::<Vec<_>>()
+        })
+    }
+
+    fn safe_area_of(metric: &impl Metric, points: &[Point], max_distance: i32) -> usize {
+        metric.safe_area_size(points, &Boundaries::from_points(points), max_distance)
+    }
+
+    #[test]
+    fn test_safe_area_outside_of_box() {
+        let single = [Point::new(5, -5)];
+
+        assert_eq!(safe_area_of(&Manhattan, &single, 3), 1 + 4 + 8);
+        assert_eq!(safe_area_of(&Chebyshev, &single, 3), 5 * 5);
+        // Euclidean metric sums squared distances
+        assert_eq!(safe_area_of(&Euclidean, &single, 3), 3 * 3);
+        assert_eq!(safe_area_of(&Manhattan, &single, 0), 0);
+        assert_eq!(safe_area_of(&Manhattan, &[], 10), 0);
+
+        let pair = [Point::new(0, 0), Point::new(2, 0)];
+
+        // cells with |x| + |x - 2| + 2|y| < 6: x in 0..=2 with |y| <= 1, and (-1, 0), (3, 0)
+        assert_eq!(safe_area_of(&Manhattan, &pair, 6), 3 * 3 + 2);
+    }
+
+    #[test]
+    fn test_chebyshev_skewed_box() {
+        // a long, flat box only grows by its height sideways, but by its width up and down
+        let points = [
+            (0, 0),
+            (240, 4),
+            (120, 2),
+            (118, 0),
+            (122, 4),
+            (60, 1),
+            (180, 3),
+            (8, 4),
+            (232, 0),
+        ]
+        .iter()
+        .map(|&(x, y)| Point::new(x, y))
+        .collect::<Vec<_>>();
+
+        let areas = areas_of(&Chebyshev, &points);
+
+        assert!(areas.iter().any(Option::is_some));
+        assert_eq!(areas, brute_force_areas(&Chebyshev, &points, 480));
+    }
+
+    proptest! {
+        #[test]
+        fn test_manhattan_safe_area_matches_brute_force(
+            points in points_strategy(10, 8),
+            max_distance in 0..120i32,
+        ) {
+            let mut expected = 0;
+            for x in -200..200 {
+                for y in -200..200 {
+                    let total = points.iter().map(|p| Manhattan.distance(p, x, y)).sum::<i64>();
+
+                    if total < i64::from(max_distance) {
+                        expected += 1;
+                    }
+                }
+            }
+
+            prop_assert_eq!(safe_area_of(&Manhattan, &points, max_distance), expected);
+        }
+
+        #[test]
+        fn test_manhattan_matches_brute_force(points in points_strategy(20, 12)) {
+            prop_assert_eq!(
+                areas_of(&Manhattan, &points),
+                brute_force_areas(&Manhattan, &points, 1)
+            );
+        }
+
+        #[test]
+        fn test_chebyshev_matches_brute_force(points in points_strategy(8, 10)) {
+            // Chebyshev areas settle beyond the box grown by its larger side
+            prop_assert_eq!(
+                areas_of(&Chebyshev, &points),
+                brute_force_areas(&Chebyshev, &points, 40)
+            );
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        #[test]
+        fn test_euclidean_finite_areas_match_brute_force(points in points_strategy(2, 10)) {
+            // lattice Voronoi vertices of finite areas lie within D^3/2 (D is the diameter)
+            // from the points, which is less than 40 for these coordinates
+            let brute_force = brute_force_areas(&Euclidean, &points, 100);
+
+            for (area, expected) in areas_of(&Euclidean, &points).into_iter().zip(brute_force) {
+                if area.is_some() {
+                    prop_assert_eq!(area, expected);
+                }
+            }
+        }
+    }
+}
@@ -1,37 +1,63 @@
 use std::collections::{HashMap, HashSet};
 
 mod boundaries;
+#[cfg(test)]
+mod fixtures;
+mod flood;
+mod metric;
+mod render;
+mod report;
+mod separable;
+
+use self::{
+    boundaries::Boundaries,
+    metric::safe_margin,
+    separable::{axis_distance_sums, count_below},
+};
 
-use self::boundaries::Boundaries;
+pub use self::metric::{Chebyshev, Euclidean, Manhattan, Metric};
 
-use super::Point;
+use super::{Point, PointN};
 
 #[derive(Debug)]
-pub struct World {
+pub struct World<M = Manhattan> {
     points: HashMap<Point, i32>,
+    // unique points in the input order
+    order: Vec<Point>,
     infinite_areas_centers: HashSet<Point>,
     boundaries: Boundaries,
+    metric: M,
 }
 
 impl World {
-    fn new() -> Self {
+    pub fn from_points(points: Vec<Point>) -> Self {
+        Self::with_metric(points, Manhattan)
+    }
+}
+
+impl<M: Metric> World<M> {
+    fn new(metric: M) -> Self {
         Self {
             points: HashMap::new(),
+            order: Vec::new(),
             infinite_areas_centers: HashSet::new(),
             boundaries: Boundaries::new(),
+            metric,
         }
     }
 
     fn insert_points(&mut self, points: Vec<Point>) {
         for point in points {
-            self.boundaries.include_point(point.x, point.y);
-
-            self.points.insert(point, 0);
+            if self.points.insert(point.clone(), 0).is_none() {
+                self.order.push(point);
+            }
         }
+
+        self.boundaries = Boundaries::from_points(&self.order);
     }
 
-    pub fn from_points(points: Vec<Point>) -> Self {
-        let mut world = Self::new();
+    pub fn with_metric(points: Vec<Point>, metric: M) -> Self {
+        let mut world = Self::new(metric);
 
         world.insert_points(points);
 
@@ -39,30 +65,34 @@ impl World {
     }
 
     fn compute_points_area(&mut self) {
-        let mut points = self.points.iter_mut().collect::<Vec<_>>();
-
-        for (x, y) in &self.boundaries {
-            let mut min_distance = i32::max_value();
-            let mut closest = None;
+        let areas = self.metric.areas(&self.order, &self.boundaries);
 
-            for (point, area) in points.iter_mut() {
-                let distance = point.distance_to(x, y);
-
-                if distance == min_distance {
-                    closest = None;
-                } else if distance < min_distance {
-                    closest.replace((point, area));
-
-                    min_distance = distance;
+        for (point, area) in self.order.iter().cloned().zip(areas) {
+            match area {
+                Some(area) => {
+                    self.points.insert(point, area);
+                }
+                None => {
+                    self.infinite_areas_centers.insert(point);
                 }
             }
+        }
+    }
 
-            if let Some((point, area)) = closest {
-                if self.boundaries.is_edge_point(x, y) {
-                    self.infinite_areas_centers.insert((*point).clone());
-                }
+    /// Indices of all the points closest to the cell, written to `nearest`
+    fn nearest(&self, x: i32, y: i32, nearest: &mut Vec<usize>) {
+        let mut min_distance = i64::MAX;
+        nearest.clear();
 
-                **area += 1;
+        for (idx, point) in self.order.iter().enumerate() {
+            let distance = self.metric.distance(point, x, y);
+
+            if distance < min_distance {
+                nearest.clear();
+                min_distance = distance;
+            }
+            if distance == min_distance {
+                nearest.push(idx);
             }
         }
     }
@@ -78,25 +108,118 @@ impl World {
     }
 
     pub fn safe_area_size(&self, max_distance: i32) -> usize {
-        let points = self.points.keys().collect::<Vec<_>>();
+        self.metric
+            .safe_area_size(&self.order, &self.boundaries, max_distance)
+    }
+}
 
-        self.boundaries
-            .into_iter()
-            .filter(|(x, y)| {
-                points
-                    .iter()
-                    .map(|point| point.distance_to(*x, *y))
-                    .sum::<i32>()
-                    < max_distance
-            })
-            .count()
+/// Number of cells of `N`-dimensional space with total Manhattan distance
+/// to all points less than `max_distance`
+pub fn safe_region_size<const N: usize>(points: &[PointN<N>], max_distance: i32) -> usize {
+    if points.is_empty() {
+        return 0;
     }
+
+    let margin = i64::from(safe_margin(points.len(), max_distance));
+    let mut axes = (0..N)
+        .map(|axis| {
+            let coords = points.iter().map(|point| point.coords[axis]);
+            let min = i64::from(coords.clone().min().unwrap()) - margin;
+            let max = i64::from(coords.clone().max().unwrap()) + margin;
+            let from = min.max(i64::from(i32::MIN));
+            let to = max.min(i64::from(i32::MAX));
+
+            axis_distance_sums(coords, from as i32, (to - from + 1) as usize)
+        })
+        .collect::<Vec<_>>();
+
+    count_below(&mut axes, i64::from(max_distance))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    use proptest::prelude::*;
+
+    use self::fixtures::{example_world, world_from, EXAMPLE};
+
+    /// Straightforward reference: point is infinite if it owns some cell just outside the box
+    fn brute_force_largest_finite_area(points: &[(i32, i32)]) -> Option<i32> {
+        let owner = |x: i32, y: i32| {
+            let distances = points
+                .iter()
+                .map(|(px, py)| (px - x).abs() + (py - y).abs())
+                .collect::<Vec<_>>();
+            let min = *distances.iter().min().unwrap();
+
+            let mut closest = distances.iter().enumerate().filter(|(_, d)| **d == min);
+
+            match (closest.next(), closest.next()) {
+                (Some((idx, _)), None) => Some(idx),
+                _ => None,
+            }
+        };
+
+        let min_x = points.iter().map(|p| p.0).min().unwrap();
+        let max_x = points.iter().map(|p| p.0).max().unwrap();
+        let min_y = points.iter().map(|p| p.1).min().unwrap();
+        let max_y = points.iter().map(|p| p.1).max().unwrap();
+
+        let mut areas = vec![0; points.len()];
+        let mut infinite = vec![false; points.len()];
+
+        for x in min_x - 1..=max_x + 1 {
+            for y in min_y - 1..=max_y + 1 {
+                let outside = x < min_x || x > max_x || y < min_y || y > max_y;
+
+                if let Some(idx) = owner(x, y) {
+                    if outside {
+                        infinite[idx] = true;
+                    } else {
+                        areas[idx] += 1;
+                    }
+                }
+            }
+        }
+
+        areas
+            .into_iter()
+            .zip(infinite)
+            .filter(|(area, infinite)| *area != 0 && !infinite)
+            .map(|(area, _)| area)
+            .max()
+    }
+
+    proptest! {
+        #[test]
+        fn test_largest_finite_area_matches_brute_force(
+            points in prop::collection::hash_set((-30..30i32, -30..30i32), 1..15)
+        ) {
+            let points = points.into_iter().collect::<Vec<_>>();
+
+            prop_assert_eq!(
+                world_from(&points).largest_finite_area(),
+                brute_force_largest_finite_area(&points)
+            );
+        }
+
+        #[test]
+        fn test_largest_finite_area_is_shift_invariant(
+            points in prop::collection::hash_set((0..30i32, 0..30i32), 1..15),
+            dx in -1000..1000i32,
+            dy in -1000..1000i32,
+        ) {
+            let points = points.into_iter().collect::<Vec<_>>();
+            let shifted = points.iter().map(|(x, y)| (x + dx, y + dy)).collect::<Vec<_>>();
+
+            prop_assert_eq!(
+                world_from(&points).largest_finite_area(),
+                world_from(&shifted).largest_finite_area()
+            );
+        }
+    }
+
     #[test]
     fn test_world_empty_inside() {
         // world with no inner points
@@ -147,6 +270,39 @@ mod tests {
         assert_eq!(world.largest_finite_area(), Some(17));
     }
 
+    #[test]
+    fn test_negative_world() {
+        // example world shifted to negative coordinates
+        let points = vec![
+            "-9, -9".parse().unwrap(),
+            "-9, -4".parse().unwrap(),
+            "-2, -7".parse().unwrap(),
+            "-7, -6".parse().unwrap(),
+            "-5, -5".parse().unwrap(),
+            "-2, -1".parse().unwrap(),
+        ];
+
+        let world = World::from_points(points);
+
+        assert_eq!(world.largest_finite_area(), Some(17));
+    }
+
+    #[test]
+    fn test_tall_world() {
+        // non-square world: middle point is enclosed only vertically by the y-bounds
+        let points = vec![
+            "0, 0".parse().unwrap(),
+            "2, 0".parse().unwrap(),
+            "1, 5".parse().unwrap(),
+            "0, 10".parse().unwrap(),
+            "2, 10".parse().unwrap(),
+        ];
+
+        let world = World::from_points(points);
+
+        assert_eq!(world.largest_finite_area(), None);
+    }
+
     #[test]
     fn test_example_world_safe() {
         // https://adventofcode.com/2018/day/6
@@ -163,4 +319,92 @@ mod tests {
 
         assert_eq!(world.safe_area_size(32), 16);
     }
+
+    #[test]
+    fn test_safe_region_2d_matches_world() {
+        let world = example_world();
+        let points = EXAMPLE
+            .iter()
+            .map(|(x, y)| PointN::new([*x, *y]))
+            .collect::<Vec<_>>();
+
+        for &max_distance in &[0, 1, 32, 100, 250] {
+            assert_eq!(
+                safe_region_size(&points, max_distance),
+                world.safe_area_size(max_distance)
+            );
+        }
+    }
+
+    #[test]
+    fn test_safe_region_3d() {
+        let single = [PointN::new([4, -4, 0])];
+        // octahedron of radius 1: center and 6 neighbours
+        assert_eq!(safe_region_size(&single, 2), 7);
+        assert_eq!(safe_region_size::<3>(&[], 2), 0);
+
+        let points = [
+            PointN::new([0, 0, 0]),
+            PointN::new([3, 1, -2]),
+            PointN::new([-1, 4, 2]),
+        ];
+
+        for &max_distance in &[0, 10, 14, 25, 40] {
+            let mut expected = 0;
+
+            for x in -30..30 {
+                for y in -30..30 {
+                    for z in -30..30 {
+                        let cell = PointN::new([x, y, z]);
+                        let total = points.iter().map(|p| p.distance_to(&cell)).sum::<i64>();
+
+                        if total < i64::from(max_distance) {
+                            expected += 1;
+                        }
+                    }
+                }
+            }
+
+            assert_eq!(
+                safe_region_size(&points, max_distance),
+                expected,
+                "max distance {}",
+                max_distance
+            );
+        }
+    }
+
+    #[test]
+    fn test_safe_area_beyond_box() {
+        // with a large enough limit every point of the box is safe and the region spills out
+        let world = example_world();
+        let brute_force = |max_distance: i64| {
+            let mut count = 0;
+
+            for x in -100..100 {
+                for y in -100..100 {
+                    let total = world
+                        .order
+                        .iter()
+                        .map(|point| i64::from(point.distance_to(x, y)))
+                        .sum::<i64>();
+
+                    if total < max_distance {
+                        count += 1;
+                    }
+                }
+            }
+
+            count
+        };
+
+        for &max_distance in &[1, 32, 60, 100, 250] {
+            assert_eq!(
+                world.safe_area_size(max_distance),
+                brute_force(i64::from(max_distance)),
+                "max distance {}",
+                max_distance
+            );
+        }
+    }
 }
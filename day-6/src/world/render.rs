@@ -0,0 +1,284 @@
+use std::{convert::TryFrom, error::Error, fmt, io::Write};
+
+use super::{boundaries::Boundaries, Metric, Point, World};
+
+const TIE_COLOR: [u8; 3] = [128, 128, 128];
+const CENTER_COLOR: [u8; 3] = [0, 0, 0];
+const OUTLINE_COLOR: [u8; 3] = [255, 255, 255];
+const MAX_LETTERS: usize = 26;
+
+#[derive(Debug)]
+pub enum RenderError {
+    TooManyPointsForLetters(usize),
+}
+
+impl fmt::Display for RenderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RenderError::TooManyPointsForLetters(n) => write!(
+                f,
+                "letter map supports at most {} points, got {}",
+                MAX_LETTERS, n
+            ),
+        }
+    }
+}
+
+impl Error for RenderError {}
+
+/// RGB image, stored row by row
+#[derive(Debug)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<[u8; 3]>,
+}
+
+impl Image {
+    pub fn write_ppm(&self, mut w: impl Write) -> Result<(), Box<dyn Error>> {
+        write!(w, "P6\n{} {}\n255\n", self.width, self.height)?;
+        w.write_all(&self.pixels.concat())?;
+
+        Ok(())
+    }
+
+    pub fn write_png(&self, w: impl Write) -> Result<(), Box<dyn Error>> {
+        let mut encoder = png::Encoder::new(w, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::RGB);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        encoder
+            .write_header()?
+            .write_image_data(&self.pixels.concat())?;
+
+        Ok(())
+    }
+}
+
+/// Distinct color for the n-th point: hues are spread by the golden angle
+fn point_color(idx: usize, dimmed: bool) -> [u8; 3] {
+    let hue = (idx as f64 * 0.618_033_988_75).fract() * 6.0;
+    let value = if dimmed { 0.4 } else { 0.95 };
+    let saturation = 0.65;
+
+    let chroma = value * saturation;
+    let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+    let (r, g, b) = match hue as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let m = value - chroma;
+    let to_byte = |c: f64| ((c + m) * 255.0).round() as u8;
+
+    [to_byte(r), to_byte(g), to_byte(b)]
+}
+
+impl<M: Metric> World<M> {
+    /// Bounding box grown by `margin`; it can't shrink, so all the points stay inside
+    fn window(&self, margin: u32) -> Boundaries {
+        self.boundaries
+            .expanded(i32::try_from(margin).unwrap_or(i32::MAX))
+    }
+
+    /// Index of the unique nearest point for every cell of the box grown by `margin`,
+    /// row by row; computed by brute force, so that it works for any metric
+    fn owners(&self, margin: u32) -> (usize, usize, Vec<Option<usize>>) {
+        let window = self.window(margin);
+        let owners = window.into_iter().map(|(x, y)| self.owner(x, y)).collect();
+
+        (window.width(), window.height(), owners)
+    }
+
+    fn owner(&self, x: i32, y: i32) -> Option<usize> {
+        let mut nearest = Vec::new();
+        self.nearest(x, y, &mut nearest);
+
+        match nearest[..] {
+            [idx] => Some(idx),
+            _ => None,
+        }
+    }
+
+    fn total_distance(&self, x: i32, y: i32) -> i64 {
+        self.order
+            .iter()
+            .map(|point| self.metric.distance(point, x, y))
+            .sum()
+    }
+
+    /// Map in the puzzle notation: point itself is an uppercase letter, its cells are lowercase,
+    /// ties are dots
+    pub fn render_letters(&self, margin: u32) -> Result<String, RenderError> {
+        if self.order.len() > MAX_LETTERS {
+            return Err(RenderError::TooManyPointsForLetters(self.order.len()));
+        }
+
+        let (width, _, owners) = self.owners(margin);
+        let (min_x, min_y) = self.window(margin).origin();
+        let mut map = String::with_capacity(owners.len() + owners.len() / width.max(1));
+
+        for (i, owner) in owners.into_iter().enumerate() {
+            let (x, y) = (min_x + (i % width) as i32, min_y + (i / width) as i32);
+
+            map.push(match owner {
+                Some(idx) => {
+                    let letter = char::from(b'a' + idx as u8);
+
+                    if self.order[idx] == Point::new(x, y) {
+                        letter.to_ascii_uppercase()
+                    } else {
+                        letter
+                    }
+                }
+                None => '.',
+            });
+
+            if (i + 1) % width == 0 {
+                map.push('\n');
+            }
+        }
+
+        Ok(map)
+    }
+
+    /// Image with a color per point, ties in grey, infinite areas dimmed
+    /// and the safe region for `max_distance` outlined
+    pub fn render_image(&self, margin: u32, max_distance: Option<i32>) -> Image {
+        let infinite = self
+            .metric
+            .areas(&self.order, &self.boundaries)
+            .into_iter()
+            .map(|area| area.is_none())
+            .collect::<Vec<_>>();
+
+        let window = self.window(margin);
+        let (min_x, min_y) = window.origin();
+        let (width, height, owners) = self.owners(margin);
+
+        let is_safe = |x: i32, y: i32| {
+            max_distance.is_some_and(|max| self.total_distance(x, y) < i64::from(max))
+        };
+
+        let mut pixels = owners
+            .iter()
+            .map(|owner| match owner {
+                Some(idx) => point_color(*idx, infinite[*idx]),
+                None => TIE_COLOR,
+            })
+            .collect::<Vec<_>>();
+
+        if max_distance.is_some() {
+            let safe = (&window)
+                .into_iter()
+                .map(|(x, y)| is_safe(x, y))
+                .collect::<Vec<_>>();
+
+            for (i, pixel) in pixels.iter_mut().enumerate() {
+                let (col, row) = (i % width, i / width);
+                let on_outline = safe[i]
+                    && (col == 0
+                        || row == 0
+                        || col + 1 == width
+                        || row + 1 == height
+                        || !safe[i - 1]
+                        || !safe[i + 1]
+                        || !safe[i - width]
+                        || !safe[i + width]);
+
+                if on_outline {
+                    *pixel = OUTLINE_COLOR;
+                }
+            }
+        }
+
+        for point in &self.order {
+            let (col, row) = ((point.x - min_x) as usize, (point.y - min_y) as usize);
+            pixels[row * width + col] = CENTER_COLOR;
+        }
+
+        Image {
+            width,
+            height,
+            pixels,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::world::fixtures::example_world;
+
+    #[test]
+    fn test_render_letters() {
+        // map from the puzzle text, which covers (0, 0) to (9, 9)
+        let expected = "\
+aaaaa.cccc
+aAaaa.cccc
+aaaddecccc
+aadddeccCc
+..dDdeeccc
+bb.deEeecc
+bBb.eeee..
+bbb.eeefff
+bbb.eeffff
+bbb.ffffFf
+";
+        // grown box spans 0..=9 by x and 0..=10 by y
+        let map = example_world().render_letters(1).unwrap();
+        let rows = map.lines().take(10).collect::<Vec<_>>();
+
+        assert_eq!(rows.join("\n") + "\n", expected);
+    }
+
+    #[test]
+    fn test_render_letters_too_many_points() {
+        let points = (0..27).map(|i| Point::new(i, 0)).collect();
+
+        assert!(World::from_points(points).render_letters(0).is_err());
+    }
+
+    #[test]
+    fn test_render_image() {
+        let world = example_world();
+        let image = world.render_image(2, Some(32));
+
+        assert_eq!((image.width, image.height), (12, 13));
+        assert_eq!(image.pixels.len(), 12 * 13);
+
+        // grown box starts at (-1, -1), so point "A" at (1, 1) is drawn at (2, 2)
+        assert_eq!(image.pixels[2 * 12 + 2], CENTER_COLOR);
+        // tie between A and B at (0, 4)
+        assert_eq!(image.pixels[5 * 12 + 1], TIE_COLOR);
+        // "A" is infinite, so its color is dimmed, "E" is finite
+        assert_eq!(image.pixels[12 + 2], point_color(0, true));
+        assert_eq!(image.pixels[5 * 12 + 6], point_color(4, false));
+        // (4, 3) is on the top edge of the safe region
+        assert_eq!(image.pixels[4 * 12 + 5], OUTLINE_COLOR);
+        assert_ne!(image.pixels[5 * 12 + 5], OUTLINE_COLOR);
+
+        let mut ppm = Vec::new();
+        image.write_ppm(&mut ppm).unwrap();
+        assert!(ppm.starts_with(b"P6\n12 13\n255\n"));
+        assert_eq!(ppm.len(), b"P6\n12 13\n255\n".len() + 12 * 13 * 3);
+
+        let mut png = Vec::new();
+        image.write_png(&mut png).unwrap();
+        assert!(png.starts_with(b"\x89PNG"));
+    }
+
+    #[test]
+    fn test_render_image_without_margin() {
+        let image = example_world().render_image(0, None);
+
+        // points on the box edges are drawn on the image edges
+        assert_eq!((image.width, image.height), (8, 9));
+        assert_eq!(image.pixels[0], CENTER_COLOR);
+        assert_eq!(image.pixels[8 * 8 + 7], CENTER_COLOR);
+    }
+}
@@ -0,0 +1,271 @@
+use std::{collections::HashMap, fmt};
+
+use super::{
+    flood::{Flood, Owner},
+    Metric, Point, World,
+};
+
+/// Area of a single input point
+#[derive(Debug, Clone, PartialEq)]
+pub struct PointArea {
+    pub point: Point,
+    /// For infinite areas only the part inside of the bounding box is counted
+    pub area: usize,
+    pub infinite: bool,
+    /// Cells inside of the bounding box, where this point tied with some others
+    pub ties: usize,
+    /// Points this one tied with and the number of such cells, most frequent first
+    pub tied_with: Vec<(Point, usize)>,
+}
+
+/// Areas of all the input points, in the input order
+#[derive(Debug, Clone, PartialEq)]
+pub struct AreaReport {
+    pub areas: Vec<PointArea>,
+}
+
+impl AreaReport {
+    /// Finite areas from the largest one, followed by the infinite ones
+    pub fn ranked(&self) -> Vec<&PointArea> {
+        let mut ranked = self.areas.iter().collect::<Vec<_>>();
+        ranked.sort_by_key(|area| (area.infinite, std::cmp::Reverse(area.area)));
+
+        ranked
+    }
+
+    /// `n`-th largest finite area, starting from zero
+    pub fn nth_largest_finite(&self, n: usize) -> Option<&PointArea> {
+        self.ranked()
+            .into_iter()
+            .filter(|area| !area.infinite)
+            .nth(n)
+    }
+}
+
+impl fmt::Display for AreaReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{:>4} | {:>24} | {:>8} | {:>6} | tied with",
+            "rank", "point", "area", "ties"
+        )?;
+
+        for (rank, area) in self.ranked().into_iter().enumerate() {
+            let point = format!("({}, {})", area.point.x, area.point.y);
+            let size = if area.infinite {
+                format!("{}+", area.area)
+            } else {
+                area.area.to_string()
+            };
+            let tied_with = area
+                .tied_with
+                .iter()
+                .map(|(other, cells)| format!("({}, {}) x{}", other.x, other.y, cells))
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            writeln!(
+                f,
+                "{:>4} | {:>24} | {:>8} | {:>6} | {}",
+                rank + 1,
+                point,
+                size,
+                area.ties,
+                tied_with
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<M: Metric> World<M> {
+    /// Per-point areas with tie statistics, ties are counted inside of the bounding box.
+    ///
+    /// If the metric can be flooded, only the tied cells are checked against every point.
+    pub fn area_report(&self) -> AreaReport {
+        let n = self.order.len();
+        let finite = self.metric.areas(&self.order, &self.boundaries);
+        let flood = self
+            .metric
+            .flood_moves()
+            .map(|moves| Flood::new(&self.boundaries, &self.order, moves));
+
+        let mut in_box = vec![0; n];
+        let mut ties = vec![0; n];
+        let mut tie_pairs = HashMap::<(usize, usize), usize>::new();
+        let mut nearest = Vec::new();
+
+        for (x, y) in &self.boundaries {
+            if let Some(Owner::Point(idx)) = flood.as_ref().map(|flood| flood.owner(x, y)) {
+                in_box[idx] += 1;
+
+                continue;
+            }
+
+            self.nearest(x, y, &mut nearest);
+
+            match nearest[..] {
+                [idx] => in_box[idx] += 1,
+                _ => {
+                    for &a in &nearest {
+                        ties[a] += 1;
+
+                        for &b in &nearest {
+                            if a != b {
+                                *tie_pairs.entry((a, b)).or_insert(0) += 1;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut tied_with = vec![Vec::new(); n];
+        for ((a, b), cells) in tie_pairs {
+            tied_with[a].push((self.order[b].clone(), cells));
+        }
+
+        let areas = tied_with
+            .into_iter()
+            .enumerate()
+            .map(|(idx, mut tied_with)| {
+                tied_with.sort_by(|(a, a_cells), (b, b_cells)| {
+                    b_cells.cmp(a_cells).then_with(|| a.partial_cmp(b).unwrap())
+                });
+
+                PointArea {
+                    point: self.order[idx].clone(),
+                    area: finite[idx].map_or(in_box[idx], |area| area as usize),
+                    infinite: finite[idx].is_none(),
+                    ties: ties[idx],
+                    tied_with,
+                }
+            })
+            .collect();
+
+        AreaReport { areas }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::world::{fixtures::example_world, Chebyshev};
+
+    #[test]
+    fn test_area_report() {
+        let report = example_world().area_report();
+
+        let finite = report
+            .areas
+            .iter()
+            .map(|area| (area.point.clone(), area.area, area.infinite))
+            .filter(|(_, _, infinite)| !infinite)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            finite,
+            vec![(Point::new(3, 4), 9, false), (Point::new(5, 5), 17, false)]
+        );
+
+        assert_eq!(
+            report.nth_largest_finite(0).unwrap().point,
+            Point::new(5, 5)
+        );
+        assert_eq!(
+            report.nth_largest_finite(1).unwrap().point,
+            Point::new(3, 4)
+        );
+        assert_eq!(report.nth_largest_finite(2), None);
+
+        let ranked = report.ranked();
+        assert_eq!(ranked[0].area, 17);
+        assert!(ranked[2..].iter().all(|area| area.infinite));
+    }
+
+    #[test]
+    fn test_area_report_ties() {
+        let report = example_world().area_report();
+
+        // A ties with E only at (5, 1), the other tie with it at (5, 0) is outside of the box
+        assert_eq!(report.areas[0].ties, 1);
+        assert_eq!(report.areas[0].tied_with, vec![(Point::new(5, 5), 1)]);
+
+        // one of F's tied cells is shared by three points
+        let f = &report.areas[5];
+        assert_eq!(f.ties, 2);
+        assert_eq!(
+            f.tied_with,
+            vec![
+                (Point::new(1, 6), 1),
+                (Point::new(3, 4), 1),
+                (Point::new(8, 3), 1),
+            ]
+        );
+        assert_eq!(report.areas[1].tied_with[0], (Point::new(3, 4), 6));
+
+        // ties are symmetric
+        for area in &report.areas {
+            for (other, cells) in &area.tied_with {
+                let other = report.areas.iter().find(|a| a.point == *other).unwrap();
+
+                assert!(other
+                    .tied_with
+                    .iter()
+                    .any(|(p, c)| *p == area.point && c == cells));
+            }
+        }
+    }
+
+    /// Tied cells of every point, found by checking every cell against every point
+    fn brute_force_ties<M: Metric>(world: &World<M>) -> Vec<usize> {
+        let mut ties = vec![0; world.order.len()];
+        let mut nearest = Vec::new();
+
+        for (x, y) in &world.boundaries {
+            world.nearest(x, y, &mut nearest);
+
+            if nearest.len() > 1 {
+                nearest.iter().for_each(|&idx| ties[idx] += 1);
+            }
+        }
+
+        ties
+    }
+
+    fn ties_of(report: &AreaReport) -> Vec<usize> {
+        report.areas.iter().map(|area| area.ties).collect()
+    }
+
+    #[test]
+    fn test_area_report_ties_match_brute_force() {
+        let points = [(0, 0), (4, 0), (0, 4), (4, 4), (2, 2), (1, 3), (3, 1)]
+            .iter()
+            .map(|(x, y)| Point::new(*x, *y))
+            .collect::<Vec<_>>();
+
+        let manhattan = World::from_points(points.clone());
+        assert_eq!(
+            ties_of(&manhattan.area_report()),
+            brute_force_ties(&manhattan)
+        );
+
+        let chebyshev = World::with_metric(points, Chebyshev);
+        assert_eq!(
+            ties_of(&chebyshev.area_report()),
+            brute_force_ties(&chebyshev)
+        );
+    }
+
+    #[test]
+    fn test_area_report_table() {
+        let table = example_world().area_report().to_string();
+        let lines = table.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines.len(), 1 + 6);
+        assert!(lines[1].contains("(5, 5)") && lines[1].contains(" 17 "));
+        assert!(lines[2].contains("(3, 4)") && lines[2].contains(" 9 "));
+        assert!(lines[3].contains('+'));
+    }
+}
@@ -0,0 +1,78 @@
+//! Counting for distances, that are sums of independent per-axis parts, like Manhattan one.
+
+/// Sums of distances from `len` consecutive positions starting at `from` to all `coords`,
+/// all of which should be not less than `from`
+pub(super) fn axis_distance_sums(
+    coords: impl Iterator<Item = i32>,
+    from: i32,
+    len: usize,
+) -> Vec<i64> {
+    let mut coords = coords.collect::<Vec<_>>();
+    coords.sort_unstable();
+
+    let n = coords.len() as i64;
+    let mut sum = coords
+        .iter()
+        .map(|c| i64::from(*c) - i64::from(from))
+        .sum::<i64>();
+    let mut passed = 0;
+    let mut sums = Vec::with_capacity(len);
+
+    for position in (0..len as i64).map(|i| i64::from(from) + i) {
+        sums.push(sum);
+
+        while passed < coords.len() && i64::from(coords[passed]) <= position {
+            passed += 1;
+        }
+
+        // stepping right gets closer to the coords ahead and further from the ones passed
+        sum += 2 * passed as i64 - n;
+    }
+
+    sums
+}
+
+/// Number of cells, whose per-axis sums add up to less than `limit`
+pub(super) fn count_below(axes: &mut [Vec<i64>], limit: i64) -> usize {
+    for axis in axes.iter_mut() {
+        axis.sort_unstable();
+    }
+
+    count_sorted(axes, limit)
+}
+
+fn count_sorted(axes: &[Vec<i64>], limit: i64) -> usize {
+    match axes {
+        [] => usize::from(limit > 0),
+        [last] => last.partition_point(|sum| *sum < limit),
+        [first, rest @ ..] => first
+            .iter()
+            .take_while(|sum| **sum < limit)
+            .map(|sum| count_sorted(rest, limit - sum))
+            .sum(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_axis_distance_sums() {
+        assert_eq!(
+            axis_distance_sums(vec![3, 0, 3].into_iter(), -1, 6),
+            vec![9, 6, 5, 4, 3, 6]
+        );
+        assert_eq!(axis_distance_sums(vec![7].into_iter(), 7, 1), vec![0]);
+    }
+
+    #[test]
+    fn test_count_below() {
+        let mut axes = vec![vec![2, 0, 1], vec![1, 0], vec![0, 3]];
+
+        // sums of the triples below 3: 0+0+0, 0+1+0, 1+0+0, 1+1+0, 2+0+0
+        assert_eq!(count_below(&mut axes, 3), 5);
+        assert_eq!(count_below(&mut axes, 0), 0);
+        assert_eq!(count_below(&mut [], 1), 1);
+    }
+}
@@ -0,0 +1,302 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt,
+};
+
+use crate::graph::{GraphError, InstructionGraph};
+
+/// Timing of a single step with unlimited workers
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct StepTiming {
+    pub name: String,
+    pub duration: i32,
+    pub earliest_start: i32,
+    /// Latest start, that doesn't delay the whole graph
+    pub latest_start: i32,
+}
+
+impl StepTiming {
+    pub(crate) fn earliest_finish(&self) -> i32 {
+        self.earliest_start + self.duration
+    }
+
+    /// How much the step can slip without delaying the whole graph
+    pub(crate) fn slack(&self) -> i32 {
+        self.latest_start - self.earliest_start
+    }
+
+    pub(crate) fn is_critical(&self) -> bool {
+        self.slack() == 0
+    }
+}
+
+/// Critical path analysis, independent of the worker count
+#[derive(Debug)]
+pub(crate) struct Analysis {
+    /// Steps in topological order, ties broken alphabetically
+    steps: Vec<StepTiming>,
+    successors: HashMap<String, Vec<String>>,
+}
+
+impl InstructionGraph {
+    /// Earliest and latest start of every step, with durations given by `cost`
+    pub(crate) fn analyse(
+        &self,
+        cost: impl Fn(&str) -> Option<i32>,
+    ) -> Result<Analysis, GraphError> {
+        self.check_acyclic()?;
+
+        let mut successors = HashMap::<String, Vec<String>>::new();
+        let mut predecessors = HashMap::<&str, Vec<&str>>::new();
+        for (from, to) in self.edges() {
+            successors
+                .entry(from.to_owned())
+                .or_default()
+                .push(to.to_owned());
+            predecessors.entry(to).or_default().push(from);
+        }
+
+        let mut in_degree = predecessors
+            .iter()
+            .map(|(node, from)| (*node, from.len()))
+            .collect::<HashMap<_, _>>();
+        let nodes = self.nodes();
+        let mut available = nodes
+            .iter()
+            .map(String::as_str)
+            .filter(|node| !in_degree.contains_key(node))
+            .collect::<BTreeSet<_>>();
+
+        let mut steps: Vec<StepTiming> = Vec::with_capacity(nodes.len());
+        let mut index = HashMap::<&str, usize>::new();
+
+        while let Some(node) = available.iter().next().cloned() {
+            available.remove(node);
+
+            let duration = cost(node).ok_or_else(|| GraphError::MissingCost(node.to_owned()))?;
+            if duration < 0 {
+                return Err(GraphError::NegativeCost(node.to_owned()));
+            }
+            let earliest_start = predecessors
+                .get(node)
+                .into_iter()
+                .flatten()
+                .map(|from| steps[index[from]].earliest_finish())
+                .max()
+                .unwrap_or(0);
+
+            index.insert(node, steps.len());
+            steps.push(StepTiming {
+                name: node.to_owned(),
+                duration,
+                earliest_start,
+                latest_start: 0,
+            });
+
+            for to in successors.get(node).into_iter().flatten() {
+                let degree = in_degree.get_mut(to.as_str()).unwrap();
+                *degree -= 1;
+
+                if *degree == 0 {
+                    available.insert(to);
+                }
+            }
+        }
+
+        let finish_time = steps
+            .iter()
+            .map(StepTiming::earliest_finish)
+            .max()
+            .unwrap_or(0);
+
+        for i in (0..steps.len()).rev() {
+            let latest_finish = successors
+                .get(&steps[i].name)
+                .into_iter()
+                .flatten()
+                .map(|to| steps[index[to.as_str()]].latest_start)
+                .min()
+                .unwrap_or(finish_time);
+
+            steps[i].latest_start = latest_finish - steps[i].duration;
+        }
+
+        Ok(Analysis { steps, successors })
+    }
+}
+
+impl Analysis {
+    pub(crate) fn steps(&self) -> &[StepTiming] {
+        &self.steps
+    }
+
+    /// Lower bound on the finish time: the finish with unlimited workers
+    pub(crate) fn finish_time(&self) -> i32 {
+        self.steps
+            .iter()
+            .map(StepTiming::earliest_finish)
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn step(&self, name: &str) -> Option<&StepTiming> {
+        self.steps.iter().find(|step| step.name == name)
+    }
+
+    /// Chain of steps with no slack from start to the finish; if there are several,
+    /// the alphabetically first one is taken at every branch
+    pub(crate) fn critical_path(&self) -> Vec<&str> {
+        let mut current = self
+            .steps
+            .iter()
+            .filter(|step| step.is_critical() && step.earliest_start == 0)
+            .min_by_key(|step| &step.name);
+        let mut path = Vec::new();
+
+        while let Some(step) = current {
+            path.push(step.name.as_str());
+
+            current = self
+                .successors
+                .get(&step.name)
+                .into_iter()
+                .flatten()
+                .filter_map(|to| self.step(to))
+                .filter(|to| to.is_critical() && to.earliest_start == step.earliest_finish())
+                .min_by_key(|to| &to.name);
+        }
+
+        path
+    }
+}
+
+/// Table of the step timings, critical steps are marked with `*`
+impl fmt::Display for Analysis {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let width = self
+            .steps
+            .iter()
+            .map(|step| step.name.len())
+            .max()
+            .unwrap_or(0)
+            .max("step".len());
+
+        writeln!(
+            f,
+            "  {:<width$} | duration | earliest | latest | slack",
+            "step",
+            width = width
+        )?;
+
+        for step in self.steps() {
+            writeln!(
+                f,
+                "{} {:<width$} | {:>8} | {:>8} | {:>6} | {:>5}",
+                if step.is_critical() { '*' } else { ' ' },
+                step.name,
+                step.duration,
+                step.earliest_start,
+                step.latest_start,
+                step.slack(),
+                width = width
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{costs::CostTable, fixtures::example_graph};
+
+    #[test]
+    fn test_analyse() {
+        let costs = CostTable::letters(0);
+        let analysis = example_graph().analyse(|step| costs.cost(step)).unwrap();
+
+        assert_eq!(
+            analysis
+                .steps()
+                .iter()
+                .map(|step| (
+                    step.name.as_str(),
+                    step.earliest_start,
+                    step.latest_start,
+                    step.slack()
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                ("C", 0, 0, 0),
+                ("A", 3, 4, 1),
+                ("B", 4, 7, 3),
+                ("D", 4, 5, 1),
+                ("F", 3, 3, 0),
+                ("E", 9, 9, 0),
+            ]
+        );
+        assert_eq!(analysis.critical_path(), vec!["C", "F", "E"]);
+        // two workers take 15 seconds, no amount of them can do better than 14
+        assert_eq!(analysis.finish_time(), 14);
+    }
+
+    #[test]
+    fn test_analyse_errors() {
+        let mut graph = example_graph();
+
+        assert_eq!(
+            graph
+                .analyse(|step| if step == "D" { None } else { Some(1) })
+                .unwrap_err(),
+            GraphError::MissingCost("D".to_owned())
+        );
+        assert_eq!(
+            graph
+                .analyse(|step| if step == "B" { Some(-1) } else { Some(1) })
+                .unwrap_err(),
+            GraphError::NegativeCost("B".to_owned())
+        );
+
+        graph.add_link("E", "C");
+        assert!(matches!(
+            graph.analyse(|_| Some(1)).unwrap_err(),
+            GraphError::Cycle(_)
+        ));
+    }
+
+    #[test]
+    fn test_parallel_critical_paths() {
+        let mut graph = InstructionGraph::new();
+
+        graph.add_link("start", "left");
+        graph.add_link("start", "right");
+        graph.add_link("left", "end");
+        graph.add_link("right", "end");
+
+        let analysis = graph.analyse(|_| Some(2)).unwrap();
+
+        assert!(analysis.steps().iter().all(StepTiming::is_critical));
+        assert_eq!(analysis.critical_path(), vec!["start", "left", "end"]);
+        assert_eq!(analysis.finish_time(), 6);
+    }
+
+    #[test]
+    fn test_display() {
+        let costs = CostTable::letters(0);
+        let table = example_graph()
+            .analyse(|step| costs.cost(step))
+            .unwrap()
+            .to_string();
+
+        assert_eq!(
+            table.lines().take(3).collect::<Vec<_>>(),
+            vec![
+                "  step | duration | earliest | latest | slack",
+                "* C    |        3 |        0 |      0 |     0",
+                "  A    |        1 |        3 |      4 |     1",
+            ]
+        );
+    }
+}
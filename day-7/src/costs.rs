@@ -0,0 +1,137 @@
+use std::{collections::HashMap, error::Error, path::Path, str::FromStr};
+
+use advent_utils::parse_file;
+
+const LETTER_A: i32 = 'A' as i32;
+
+/// Compute letter number (only uppercased ASCII)
+///
+/// ```
+/// assert_eq!(letter_number('A'), 1);
+/// assert_eq!(letter_number('Z'), 26);
+/// ```
+fn letter_number(letter: char) -> i32 {
+    debug_assert!(letter <= 'Z');
+    debug_assert!(letter >= 'A');
+    letter as i32 - LETTER_A + 1
+}
+
+/// Line of the cost table file: `compile-core 30`
+#[derive(Debug, PartialEq)]
+pub(crate) struct StepCost {
+    pub name: String,
+    pub cost: i32,
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum StepCostParseError {
+    InvalidInput(String),
+}
+
+impl FromStr for StepCost {
+    type Err = StepCostParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || StepCostParseError::InvalidInput(s.to_owned());
+        let mut parts = s.split_whitespace();
+
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(name), Some(cost), None) => {
+                let cost = cost.parse().map_err(|_| invalid())?;
+
+                if cost < 0 {
+                    return Err(invalid());
+                }
+
+                Ok(StepCost {
+                    name: name.to_owned(),
+                    cost,
+                })
+            }
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// Durations of the steps
+#[derive(Debug, Clone, Default)]
+pub(crate) struct CostTable {
+    costs: HashMap<String, i32>,
+}
+
+impl CostTable {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// Puzzle costs: step `A` takes `delta + 1`, `B` takes `delta + 2` and so on
+    pub(crate) fn letters(delta: i32) -> Self {
+        let mut table = Self::new();
+
+        for letter in 'A'..='Z' {
+            table.set(&letter.to_string(), letter_number(letter) + delta);
+        }
+
+        table
+    }
+
+    pub(crate) fn from_file<P: AsRef<Path>>(p: P) -> Result<Self, Box<dyn Error>> {
+        let mut table = Self::new();
+
+        for StepCost { name, cost } in parse_file(p)? {
+            table.set(&name, cost);
+        }
+
+        Ok(table)
+    }
+
+    pub(crate) fn set(&mut self, step: &str, cost: i32) {
+        self.costs.insert(step.to_owned(), cost);
+    }
+
+    pub(crate) fn cost(&self, step: &str) -> Option<i32> {
+        self.costs.get(step).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_letter_number() {
+        assert_eq!(letter_number('A'), 1);
+        assert_eq!(letter_number('Z'), 26);
+    }
+
+    #[test]
+    fn test_letters() {
+        let table = CostTable::letters(60);
+
+        assert_eq!(table.cost("A"), Some(61));
+        assert_eq!(table.cost("Z"), Some(86));
+        assert_eq!(table.cost("AA"), None);
+    }
+
+    #[test]
+    fn test_step_cost_from_str() {
+        assert_eq!(
+            "compile-core 30".parse(),
+            Ok(StepCost {
+                name: "compile-core".to_owned(),
+                cost: 30,
+            })
+        );
+        assert_eq!(
+            "  link\t5 ".parse::<StepCost>().map(|step| step.cost),
+            Ok(5)
+        );
+
+        for invalid in &["", "link", "link five", "link 5 6", "link -1"] {
+            assert_eq!(
+                invalid.parse::<StepCost>(),
+                Err(StepCostParseError::InvalidInput((*invalid).to_owned()))
+            );
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use std::{collections::HashMap, fmt::Write};
+
+use crate::{
+    graph::{GraphError, InstructionGraph},
+    traversers::ScheduledStep,
+};
+
+fn escape(name: &str) -> String {
+    name.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn quote(name: &str) -> String {
+    format!("\"{}\"", escape(name))
+}
+
+/// Step graph in Graphviz DOT format
+pub(crate) fn graph_to_dot(graph: &InstructionGraph) -> String {
+    let mut dot = String::from("digraph steps {\n");
+
+    for node in graph.nodes() {
+        writeln!(dot, "    {};", quote(&node)).unwrap();
+    }
+    for (from, to) in graph.edges() {
+        writeln!(dot, "    {} -> {};", quote(from), quote(to)).unwrap();
+    }
+
+    dot.push_str("}\n");
+
+    dot
+}
+
+/// Schedule in Graphviz DOT format: steps are annotated with their start and finish times,
+/// critical path of the graph with the scheduled durations is highlighted
+pub(crate) fn schedule_to_dot(
+    graph: &InstructionGraph,
+    schedule: &[ScheduledStep],
+) -> Result<String, GraphError> {
+    let durations = schedule
+        .iter()
+        .map(|step| (step.name.as_str(), step.finish - step.start))
+        .collect::<HashMap<_, _>>();
+    let analysis = graph.analyse(|step| durations.get(step).cloned())?;
+    let critical = analysis.critical_path();
+    let is_critical = |node: &str| critical.contains(&node);
+
+    let mut dot = String::from("digraph schedule {\n    rankdir=LR;\n");
+
+    for step in schedule {
+        write!(
+            dot,
+            "    {} [label=\"{}\\n{}..{}\"",
+            quote(&step.name),
+            escape(&step.name),
+            step.start,
+            step.finish
+        )
+        .unwrap();
+
+        if is_critical(&step.name) {
+            dot.push_str(", color=red, penwidth=2");
+        }
+
+        dot.push_str("];\n");
+    }
+
+    for (from, to) in graph.edges() {
+        let on_path = critical
+            .windows(2)
+            .any(|pair| pair[0] == from && pair[1] == to);
+
+        write!(dot, "    {} -> {}", quote(from), quote(to)).unwrap();
+
+        if on_path {
+            dot.push_str(" [color=red, penwidth=2]");
+        }
+
+        dot.push_str(";\n");
+    }
+
+    dot.push_str("}\n");
+
+    Ok(dot)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::fixtures::example_schedule;
+
+    /// Graph where F runs alongside the critical path C, B, G
+    fn slack_graph() -> InstructionGraph {
+        let mut graph = InstructionGraph::new();
+
+        graph.add_link("C", "F");
+        graph.add_link("C", "B");
+        graph.add_link("B", "G");
+
+        graph
+    }
+
+    #[test]
+    fn test_graph_to_dot() {
+        assert_eq!(
+            graph_to_dot(&slack_graph()),
+            "digraph steps {
+    \"B\";
+    \"C\";
+    \"F\";
+    \"G\";
+    \"B\" -> \"G\";
+    \"C\" -> \"B\";
+    \"C\" -> \"F\";
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("compile-core"), "\"compile-core\"");
+        assert_eq!(quote("say \"hi\""), "\"say \\\"hi\\\"\"");
+    }
+
+    #[test]
+    fn test_schedule_to_dot() {
+        let graph = slack_graph();
+        // C: 0..3, B: 3..5, F: 3..9, G: 5..12, so F has slack
+        let dot = schedule_to_dot(&graph, &example_schedule(&graph)).unwrap();
+
+        assert!(dot.starts_with("digraph schedule {\n"));
+        assert!(dot.contains("    \"G\" [label=\"G\\n5..12\", color=red, penwidth=2];\n"));
+        assert!(dot.contains("    \"F\" [label=\"F\\n3..9\"];\n"));
+        assert!(dot.contains("    \"B\" -> \"G\" [color=red, penwidth=2];\n"));
+        assert!(dot.contains("    \"C\" -> \"F\";\n"));
+
+        // every step of the graph has to be scheduled
+        assert_eq!(
+            schedule_to_dot(&graph, &[]).unwrap_err(),
+            GraphError::MissingCost("C".to_owned())
+        );
+    }
+}
@@ -0,0 +1,33 @@
+use std::num::NonZeroUsize;
+
+use crate::{
+    costs::CostTable,
+    graph::InstructionGraph,
+    traversers::{PooledTraverser, ScheduledStep},
+};
+
+/// Graph from the puzzle text: https://adventofcode.com/2018/day/7
+pub(crate) fn example_graph() -> InstructionGraph {
+    let mut graph = InstructionGraph::new();
+
+    graph.add_link("C", "A");
+    graph.add_link("C", "F");
+    graph.add_link("A", "B");
+    graph.add_link("A", "D");
+    graph.add_link("B", "E");
+    graph.add_link("D", "E");
+    graph.add_link("F", "E");
+
+    graph
+}
+
+/// Schedule of `graph` for two workers, with letter costs and no base cost
+pub(crate) fn example_schedule(graph: &InstructionGraph) -> Vec<ScheduledStep> {
+    PooledTraverser::new(graph, CostTable::letters(0), NonZeroUsize::new(2).unwrap())
+        .and_then(PooledTraverser::schedule)
+        .unwrap()
+}
+
+pub(crate) fn names(names: &[&str]) -> Vec<String> {
+    names.iter().map(|name| (*name).to_owned()).collect()
+}
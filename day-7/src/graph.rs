@@ -1,19 +1,87 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, error::Error, fmt};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub(crate) struct Instruction {
-    name: char,
-    outbound: Vec<char>,
-    inbound: Vec<char>,
+    name: String,
+    outbound: Vec<String>,
+    inbound: Vec<String>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub(crate) struct InstructionGraph {
-    nodes: HashMap<char, Instruction>,
+    nodes: HashMap<String, Instruction>,
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum GraphError {
+    /// Steps forming a cycle, each one depends on the previous one
+    Cycle(Vec<String>),
+    MissingNode(String),
+    /// Traversal ran out of available steps with these ones still pending
+    Stuck(Vec<String>),
+    /// Step has no duration in the cost table
+    MissingCost(String),
+    /// Step's duration is below zero
+    NegativeCost(String),
+    /// There's no such link between the steps
+    MissingLink(String, String),
+    /// No workers in the pool, or not enough of them, can perform the step
+    NoCapableWorkers(String),
+    /// Step is required to be done by no workers at all
+    NoWorkersRequired(String),
+    /// Graph is too large for an exhaustive analysis
+    TooManySteps(usize),
+    /// Steps can be completed in too many different combinations to keep track of them
+    TooManyStepSets(usize),
+}
+
+impl fmt::Display for GraphError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GraphError::Cycle(steps) => {
+                write!(f, "steps form a cycle: ")?;
+
+                for step in steps {
+                    write!(f, "{} -> ", step)?;
+                }
+
+                write!(f, "{}", steps[0])
+            }
+            GraphError::MissingNode(step) => write!(f, "step {} is not in the graph", step),
+            GraphError::Stuck(steps) => write!(
+                f,
+                "traversal got stuck with steps {} left",
+                steps.join(", ")
+            ),
+            GraphError::MissingCost(step) => write!(f, "step {} has no cost", step),
+            GraphError::NegativeCost(step) => write!(f, "step {} has a negative cost", step),
+            GraphError::MissingLink(from, to) => {
+                write!(f, "step {} doesn't depend on {}", to, from)
+            }
+            GraphError::NoCapableWorkers(step) => {
+                write!(f, "no workers can perform step {}", step)
+            }
+            GraphError::NoWorkersRequired(step) => {
+                write!(f, "step {} has to require at least one worker", step)
+            }
+            GraphError::TooManySteps(n) => write!(f, "graph has too many steps: {}", n),
+            GraphError::TooManyStepSets(limit) => {
+                write!(f, "graph has more than {} sets of completed steps", limit)
+            }
+        }
+    }
+}
+
+impl Error for GraphError {}
+
+#[derive(Clone, Copy, PartialEq)]
+enum VisitState {
+    InProgress,
+    Done,
 }
 
 impl Instruction {
-    pub fn new(name: char) -> Self {
+    pub fn new(name: String) -> Self {
         Self {
             name,
             outbound: Vec::new(),
@@ -21,12 +89,12 @@ impl Instruction {
         }
     }
 
-    pub fn add_new_link(&mut self, to: char) {
-        self.outbound.push(to)
+    pub fn add_new_link(&mut self, to: &str) {
+        self.outbound.push(to.to_owned())
     }
 
-    pub fn set_incoming(&mut self, from: char) {
-        self.inbound.push(from);
+    pub fn set_incoming(&mut self, from: &str) {
+        self.inbound.push(from.to_owned());
     }
 }
 
@@ -37,42 +105,196 @@ impl InstructionGraph {
         }
     }
 
-    pub(crate) fn add_link(&mut self, from: char, to: char) {
+    pub(crate) fn add_link(&mut self, from: &str, to: &str) {
         self.nodes
-            .entry(to)
-            .or_insert_with(|| Instruction::new(to))
+            .entry(to.to_owned())
+            .or_insert_with(|| Instruction::new(to.to_owned()))
             .set_incoming(from);
         let from_node = self
             .nodes
-            .entry(from)
-            .or_insert_with(|| Instruction::new(from));
+            .entry(from.to_owned())
+            .or_insert_with(|| Instruction::new(from.to_owned()));
 
         from_node.add_new_link(to)
     }
 
-    pub(crate) fn get_starting_nodes<'a>(&'a self) -> impl Iterator<Item = char> + 'a {
-        self.nodes.iter().filter_map(|(c, node)| {
+    /// Remove the link, returning whether it was there
+    pub(crate) fn remove_link(&mut self, from: &str, to: &str) -> bool {
+        let outbound = match self.nodes.get_mut(from) {
+            Some(node) => &mut node.outbound,
+            None => return false,
+        };
+        match outbound.iter().position(|next| next == to) {
+            Some(i) => outbound.remove(i),
+            None => return false,
+        };
+
+        let inbound = &mut self.nodes.get_mut(to).unwrap().inbound;
+        let i = inbound.iter().position(|prev| prev == from).unwrap();
+        inbound.remove(i);
+
+        true
+    }
+
+    pub(crate) fn contains(&self, node: &str) -> bool {
+        self.nodes.contains_key(node)
+    }
+
+    /// Steps, that have to be done before the given one
+    pub(crate) fn dependencies(&self, node: &str) -> &[String] {
+        self.nodes
+            .get(node)
+            .map_or(&[], |node| node.inbound.as_slice())
+    }
+
+    pub(crate) fn get_starting_nodes<'a>(&'a self) -> impl Iterator<Item = String> + 'a {
+        self.nodes.values().filter_map(|node| {
             if node.inbound.is_empty() {
-                Some(*c)
+                Some(node.name.clone())
             } else {
                 None
             }
         })
     }
 
-    pub(crate) fn visit_node(&mut self, node: char) -> Vec<char> {
-        let instruction = self.nodes.remove(&node).unwrap();
+    /// All the steps, in alphabetical order
+    pub(crate) fn nodes(&self) -> Vec<String> {
+        let mut remaining = self.nodes.keys().cloned().collect::<Vec<_>>();
+        remaining.sort();
 
-        instruction
-            .outbound
-            .into_iter()
-            .filter(|to| {
-                let dest = self.nodes.get_mut(to).unwrap();
+        remaining
+    }
 
-                dest.inbound.retain(|from| from != &node);
-                dest.inbound.is_empty()
+    /// All `(from, to)` links, sorted
+    pub(crate) fn edges(&self) -> Vec<(&str, &str)> {
+        let mut edges = self
+            .nodes
+            .values()
+            .flat_map(|node| {
+                node.outbound
+                    .iter()
+                    .map(move |to| (node.name.as_str(), to.as_str()))
             })
-            .collect()
+            .collect::<Vec<_>>();
+        edges.sort();
+
+        edges
+    }
+
+    /// Find some cycle with depth-first search, starting from the alphabetically first steps
+    pub(crate) fn find_cycle(&self) -> Option<Vec<String>> {
+        let mut states = HashMap::new();
+        let mut starts = self.nodes.keys().map(String::as_str).collect::<Vec<_>>();
+        starts.sort();
+
+        for start in starts {
+            if states.contains_key(start) {
+                continue;
+            }
+
+            // current DFS path: node and the index of its next outbound link to check
+            let mut path = vec![(start, 0)];
+            states.insert(start, VisitState::InProgress);
+
+            while let Some(&(node, next)) = path.last() {
+                let outbound = &self.nodes[node].outbound;
+
+                if next == outbound.len() {
+                    states.insert(node, VisitState::Done);
+                    path.pop();
+
+                    continue;
+                }
+
+                path.last_mut().unwrap().1 += 1;
+                let to = outbound[next].as_str();
+
+                match states.get(to) {
+                    Some(VisitState::InProgress) => {
+                        let cycle_start = path.iter().position(|(n, _)| *n == to).unwrap();
+
+                        return Some(
+                            path[cycle_start..]
+                                .iter()
+                                .map(|(n, _)| (*n).to_owned())
+                                .collect(),
+                        );
+                    }
+                    Some(VisitState::Done) => {}
+                    None => {
+                        states.insert(to, VisitState::InProgress);
+                        path.push((to, 0));
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+    pub(crate) fn check_acyclic(&self) -> Result<(), GraphError> {
+        match self.find_cycle() {
+            Some(cycle) => Err(GraphError::Cycle(cycle)),
+            None => Ok(()),
+        }
+    }
+
+    /// Start a new traversal, leaving the graph itself intact
+    pub(crate) fn traversal(&self) -> Traversal<'_> {
+        Traversal {
+            graph: self,
+            pending: self
+                .nodes
+                .iter()
+                .map(|(name, node)| (name.as_str(), node.inbound.len()))
+                .collect(),
+        }
+    }
+}
+
+/// State of a single traversal: number of unfinished dependencies for every step not visited yet
+#[derive(Debug, Clone)]
+pub(crate) struct Traversal<'a> {
+    graph: &'a InstructionGraph,
+    pending: HashMap<&'a str, usize>,
+}
+
+impl<'a> Traversal<'a> {
+    /// Steps not visited yet, in alphabetical order
+    pub(crate) fn remaining_nodes(&self) -> Vec<String> {
+        let mut remaining = self
+            .pending
+            .keys()
+            .map(|&node| node.to_owned())
+            .collect::<Vec<_>>();
+        remaining.sort();
+
+        remaining
+    }
+
+    /// Mark the step as done, returning steps, that have no unfinished dependencies left
+    pub(crate) fn visit_node(&mut self, node: &str) -> Result<Vec<String>, GraphError> {
+        if self.pending.remove(node).is_none() {
+            return Err(GraphError::MissingNode(node.to_owned()));
+        }
+
+        let instruction = &self.graph.nodes[node];
+
+        let mut available = Vec::new();
+
+        for to in &instruction.outbound {
+            let pending = match self.pending.get_mut(to.as_str()) {
+                Some(pending) => pending,
+                None => return Err(GraphError::MissingNode(to.clone())),
+            };
+
+            *pending -= 1;
+            if *pending == 0 {
+                available.push(to.clone());
+            }
+        }
+
+        Ok(available)
     }
 }
 
@@ -80,14 +302,110 @@ impl InstructionGraph {
 mod tests {
     use super::*;
 
+    use crate::fixtures::names;
+
     #[test]
     fn test_get_starting_node() {
         let mut graph = InstructionGraph::new();
 
-        graph.add_link('C', 'F');
-        graph.add_link('C', 'B');
-        graph.add_link('B', 'G');
+        graph.add_link("C", "F");
+        graph.add_link("C", "B");
+        graph.add_link("B", "G");
+
+        assert_eq!(
+            graph.get_starting_nodes().collect::<Vec<_>>(),
+            vec!["C".to_owned()]
+        );
+    }
+
+    #[test]
+    fn test_find_cycle() {
+        let mut graph = InstructionGraph::new();
+
+        graph.add_link("C", "F");
+        graph.add_link("C", "B");
+        graph.add_link("B", "G");
+        assert_eq!(graph.find_cycle(), None);
+        assert_eq!(graph.check_acyclic(), Ok(()));
+
+        graph.add_link("G", "X");
+        graph.add_link("X", "B");
+        assert_eq!(graph.find_cycle(), Some(names(&["B", "G", "X"])));
+        assert_eq!(
+            graph.check_acyclic().unwrap_err().to_string(),
+            "steps form a cycle: B -> G -> X -> B"
+        );
+
+        let mut graph = InstructionGraph::new();
+        graph.add_link("A", "A");
+        assert_eq!(graph.find_cycle(), Some(names(&["A"])));
+    }
+
+    #[test]
+    fn test_visit_node() {
+        let mut graph = InstructionGraph::new();
+
+        graph.add_link("C", "F");
+        graph.add_link("C", "B");
+        graph.add_link("F", "B");
+
+        let mut traversal = graph.traversal();
+
+        assert_eq!(traversal.visit_node("C"), Ok(names(&["F"])));
+        assert_eq!(
+            traversal.visit_node("C"),
+            Err(GraphError::MissingNode("C".to_owned()))
+        );
+        assert_eq!(traversal.remaining_nodes(), names(&["B", "F"]));
+        assert_eq!(traversal.visit_node("F"), Ok(names(&["B"])));
+
+        // graph is left intact, so it can be traversed again
+        assert_eq!(graph.nodes(), names(&["B", "C", "F"]));
+        assert_eq!(graph.traversal().remaining_nodes(), names(&["B", "C", "F"]));
+    }
+
+    #[test]
+    fn test_remove_link() {
+        let mut graph = InstructionGraph::new();
+
+        graph.add_link("C", "F");
+        graph.add_link("C", "B");
+        graph.add_link("F", "B");
+
+        assert!(graph.remove_link("C", "B"));
+        assert!(!graph.remove_link("C", "B"));
+        assert!(!graph.remove_link("X", "B"));
+        assert_eq!(graph.edges(), vec![("C", "F"), ("F", "B")]);
+        assert_eq!(graph.dependencies("B"), &names(&["F"])[..]);
+        assert!(graph.contains("C"));
+    }
+
+    #[test]
+    fn test_edges() {
+        let mut graph = InstructionGraph::new();
+
+        graph.add_link("C", "F");
+        graph.add_link("C", "B");
+        graph.add_link("B", "G");
+
+        assert_eq!(graph.edges(), vec![("B", "G"), ("C", "B"), ("C", "F")]);
+    }
+
+    #[test]
+    fn test_multi_character_names() {
+        let mut graph = InstructionGraph::new();
+
+        graph.add_link("compile-core", "link");
+        graph.add_link("compile-ui", "link");
+        graph.add_link("link", "compile-core");
 
-        assert_eq!(graph.get_starting_nodes().collect::<Vec<_>>(), vec!['C']);
+        assert_eq!(
+            graph.get_starting_nodes().collect::<Vec<_>>(),
+            names(&["compile-ui"])
+        );
+        assert_eq!(
+            graph.check_acyclic().unwrap_err().to_string(),
+            "steps form a cycle: compile-core -> link -> compile-core"
+        );
     }
 }
@@ -1,26 +1,30 @@
 use std::str::FromStr;
 
-use nom::{anychar, delimited, map, named, separated_pair, tag};
+use nom::{delimited, is_not, map, named, separated_pair, tag};
 
 #[derive(Debug, PartialEq)]
 pub(crate) struct OperationOrder {
-    pub first: char,
-    pub second: char,
+    pub first: String,
+    pub second: String,
 }
 
 // Step C must be finished before step A can begin.
+// Step compile-core must be finished before step link can begin.
 named!(parse_instruction<&str, OperationOrder>,
     map!(
         delimited!(
             tag!("Step "),
             separated_pair!(
-                anychar,
+                is_not!(" "),
                 tag!(" must be finished before step "),
-                anychar
+                is_not!(" ")
             ),
             tag!(" can begin.")
         ),
-        |(first, second)| OperationOrder { first, second }
+        |(first, second)| OperationOrder {
+            first: first.to_owned(),
+            second: second.to_owned(),
+        }
     )
 );
 
@@ -43,6 +47,19 @@ impl FromStr for OperationOrder {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_multi_character_names() {
+        assert_eq!(
+            "Step compile-core must be finished before step link can begin."
+                .parse::<OperationOrder>()
+                .unwrap(),
+            OperationOrder {
+                first: "compile-core".to_owned(),
+                second: "link".to_owned(),
+            }
+        );
+    }
+
     #[test]
     fn test_from_str() {
         assert_eq!(
@@ -50,16 +67,29 @@ mod tests {
                 .parse::<OperationOrder>()
                 .unwrap(),
             OperationOrder {
-                first: 'C',
-                second: 'A',
+                first: "C".to_owned(),
+                second: "A".to_owned(),
+            }
+        );
+
+        assert_eq!(
+            "Step CDF must be finished before step A can begin."
+                .parse::<OperationOrder>()
+                .unwrap(),
+            OperationOrder {
+                first: "CDF".to_owned(),
+                second: "A".to_owned(),
             }
         );
 
         assert_eq!(
-            "Step CDF must be finished before step A can begin.".parse::<OperationOrder>(),
+            "Step C D must be finished before step A can begin.".parse::<OperationOrder>(),
             Err(OperationOrderParseError::InvalidInput(
-                "Step CDF must be finished before step A can begin.".to_owned()
+                "Step C D must be finished before step A can begin.".to_owned()
             )),
         );
+        assert!("Step  must be finished before step A can begin."
+            .parse::<OperationOrder>()
+            .is_err());
     }
 }
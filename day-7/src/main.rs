@@ -1,18 +1,45 @@
-use std::{error::Error, num::NonZeroUsize};
+use std::{collections::HashMap, convert::TryFrom, error::Error, fs, num::NonZeroUsize};
 
 use serde::Deserialize;
 
 use advent_utils::{get_custom_config, Part};
 
+mod analysis;
+mod costs;
+mod dot;
+#[cfg(test)]
+mod fixtures;
 mod graph;
 mod instruction;
+mod orders;
 mod parser;
+mod timeline;
 mod workers;
 
 mod traversers;
 
+use self::costs::CostTable;
+use self::dot::{graph_to_dot, schedule_to_dot};
+use self::graph::InstructionGraph;
+use self::orders::{check_order, count_orders, split_order, TopologicalOrders};
 use self::parser::parse_graph;
-use self::traversers::{PooledTraverser, SimpleTraverser};
+use self::timeline::{join_steps, render_gantt, render_table, utilisation};
+use self::traversers::{PooledTraverser, Rescheduler, ScheduleChange, SimpleTraverser};
+use self::workers::{Pool, StepRequirement, WorkerClass};
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum DotExport {
+    Graph,
+    Schedule,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Timeline {
+    Table,
+    Gantt,
+}
 
 #[derive(Debug, Deserialize)]
 struct Config {
@@ -20,28 +47,230 @@ struct Config {
     part: Part,
     work_price_delta: Option<i32>,
     workers: Option<NonZeroUsize>,
+    costs_file: Option<String>,
+    #[serde(default)]
+    costs: HashMap<String, i32>,
+    dot: Option<DotExport>,
+    dot_file: Option<String>,
+    timeline: Option<Timeline>,
+    gantt_scale: Option<i32>,
+    #[serde(default)]
+    analysis: bool,
+    #[serde(default)]
+    count_orders: bool,
+    list_orders: Option<usize>,
+    check_order: Option<String>,
+    #[serde(default)]
+    worker_classes: Vec<WorkerClass>,
+    #[serde(default)]
+    requirements: HashMap<String, StepRequirement>,
+    #[serde(default)]
+    changes: Vec<ScheduleChange>,
+}
+
+fn n_workers(c: &Config) -> NonZeroUsize {
+    c.workers.or_else(|| NonZeroUsize::new(2)).unwrap()
+}
+
+/// Workers of the configured classes, or as many identical ones as configured
+fn pool(c: &Config) -> Result<Pool, Box<dyn Error>> {
+    if c.worker_classes.is_empty() {
+        return Ok(Pool::new(n_workers(c)));
+    }
+
+    Pool::from_classes(&c.worker_classes).ok_or_else(|| "worker classes have no workers".into())
+}
+
+/// Costs from the file and config, the latter taking precedence;
+/// puzzle letter-based costs if neither is given
+fn cost_table(c: &Config) -> Result<CostTable, Box<dyn Error>> {
+    if c.costs_file.is_none() && c.costs.is_empty() {
+        let delta = c
+            .work_price_delta
+            .expect("Work price delta should be specified for letter-based costs");
+
+        return Ok(CostTable::letters(delta));
+    }
+
+    let mut table = match &c.costs_file {
+        Some(path) => CostTable::from_file(path)?,
+        None => CostTable::new(),
+    };
+
+    for (step, cost) in &c.costs {
+        table.set(step, *cost);
+    }
+
+    Ok(table)
+}
+
+fn pooled_traverser<'a>(
+    c: &Config,
+    graph: &'a InstructionGraph,
+) -> Result<PooledTraverser<'a>, Box<dyn Error>> {
+    let costs = cost_table(c)?;
+
+    let traverser = if c.worker_classes.is_empty() && c.requirements.is_empty() {
+        PooledTraverser::new(graph, costs, n_workers(c))?
+    } else {
+        PooledTraverser::with_pool(graph, costs, pool(c)?, c.requirements.clone())?
+    };
+
+    Ok(traverser)
+}
+
+/// Write DOT to the configured file, or to stdout if there's none
+fn export_dot(c: &Config, graph: &InstructionGraph) -> Result<(), Box<dyn Error>> {
+    let dot = match c.dot {
+        Some(DotExport::Graph) => graph_to_dot(graph),
+        Some(DotExport::Schedule) => {
+            let schedule = pooled_traverser(c, graph)?.schedule()?;
+
+            schedule_to_dot(graph, &schedule)?
+        }
+        None => return Ok(()),
+    };
+
+    match &c.dot_file {
+        Some(path) => fs::write(path, dot)?,
+        None => print!("{}", dot),
+    }
+
+    Ok(())
+}
+
+fn print_timeline(
+    c: &Config,
+    timeline: Timeline,
+    traverser: PooledTraverser,
+) -> Result<(), Box<dyn Error>> {
+    let schedule = traverser.schedule()?;
+    let n_workers = pool(c)?.size();
+
+    match timeline {
+        Timeline::Table => print!("{}", render_table(&schedule, n_workers)),
+        Timeline::Gantt => print!(
+            "{}",
+            render_gantt(&schedule, n_workers, c.gantt_scale.unwrap_or(1))
+        ),
+    }
+
+    for (worker, usage) in utilisation(&schedule, n_workers).iter().enumerate() {
+        println!(
+            "Worker {} was busy for {} of {} seconds ({:.1}%)",
+            worker + 1,
+            usage.busy,
+            usage.total,
+            usage.ratio() * 100.0
+        );
+    }
+
+    println!(
+        "work will be all done at: {}",
+        schedule.iter().map(|step| step.finish).max().unwrap_or(0)
+    );
+
+    Ok(())
+}
+
+fn print_orders(c: &Config, graph: &InstructionGraph) -> Result<(), Box<dyn Error>> {
+    if c.count_orders {
+        println!("valid orders: {}", count_orders(graph)?);
+    }
+
+    if let Some(limit) = c.list_orders {
+        for order in TopologicalOrders::new(graph)?.take(limit) {
+            println!("{}", join_steps(&order));
+        }
+    }
+
+    if let Some(order) = &c.check_order {
+        match check_order(graph, &split_order(order)) {
+            Ok(()) => println!("{} is a valid order", order),
+            Err(e) => println!("{} is not a valid order: {}", order, e),
+        }
+    }
+
+    Ok(())
+}
+
+fn print_analysis(c: &Config, graph: &InstructionGraph) -> Result<(), Box<dyn Error>> {
+    let costs = cost_table(c)?;
+    let analysis = graph.analyse(|step| costs.cost(step))?;
+
+    print!("{}", analysis);
+    println!("critical path: {}", analysis.critical_path().join(" -> "));
+    println!(
+        "with unlimited workers, work will be all done at: {}",
+        analysis.finish_time()
+    );
+
+    Ok(())
+}
+
+/// Apply the configured changes one by one, showing the steps, that moved
+fn print_changes(c: &Config, graph: &InstructionGraph) -> Result<(), Box<dyn Error>> {
+    let mut rescheduler = Rescheduler::new(
+        graph.clone(),
+        cost_table(c)?,
+        pool(c)?,
+        c.requirements.clone(),
+    )?;
+
+    println!("work will be all done at: {}", rescheduler.finish_time());
+
+    for change in &c.changes {
+        println!("{:?}", change);
+
+        for moved in rescheduler.apply(change)? {
+            println!(
+                "    {}: {}..{} -> {}..{}",
+                moved.after.name,
+                moved.before.start,
+                moved.before.finish,
+                moved.after.start,
+                moved.after.finish
+            );
+        }
+
+        println!("work will be all done at: {}", rescheduler.finish_time());
+    }
+
+    Ok(())
 }
 
 fn main() -> Result<(), Box<dyn Error>> {
     let c = get_custom_config::<Config>()?;
-    let graph = parse_graph(c.input_file);
+    let graph = parse_graph(&c.input_file)?;
+
+    export_dot(&c, &graph)?;
 
     match c.part {
         Part::One => {
-            let traverser = SimpleTraverser::from(graph);
+            print_orders(&c, &graph)?;
+
+            let order = SimpleTraverser::try_from(&graph)?.collect::<Result<Vec<_>, _>>()?;
 
-            println!("Suggested work order is: {}", traverser.collect::<String>());
+            println!("Suggested work order is: {}", join_steps(&order));
         }
         Part::Two => {
-            let delta = c.work_price_delta.unwrap();
-            let workers = c.workers.or_else(|| NonZeroUsize::new(2)).unwrap();
+            if c.analysis {
+                print_analysis(&c, &graph)?;
+            }
 
-            let traverser = PooledTraverser::new(graph, delta, workers);
+            if !c.changes.is_empty() {
+                print_changes(&c, &graph)?;
+            }
 
-            println!(
-                "work will be all done at: {}",
-                traverser.graph_finish_time()
-            )
+            let traverser = pooled_traverser(&c, &graph)?;
+
+            match c.timeline {
+                Some(timeline) => print_timeline(&c, timeline, traverser)?,
+                None => println!(
+                    "work will be all done at: {}",
+                    traverser.graph_finish_time()?
+                ),
+            }
         }
     }
 
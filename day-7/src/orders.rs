@@ -0,0 +1,431 @@
+use std::{collections::HashMap, error::Error, fmt};
+
+use crate::graph::{GraphError, InstructionGraph};
+
+/// Counting uses a bit per step, and 32! still fits into `u128`
+const MAX_COUNTED_STEPS: usize = 32;
+/// Number of step sets to remember: loosely linked graphs have up to `2^n` of them,
+/// so counting stops once there are more
+const MAX_COUNTED_STATES: usize = 1 << 18;
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum OrderError {
+    UnknownStep(String),
+    Repeated(String),
+    /// Step comes before one of its dependencies
+    Dependency {
+        step: String,
+        requires: String,
+    },
+    Missing(Vec<String>),
+}
+
+impl fmt::Display for OrderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OrderError::UnknownStep(step) => write!(f, "step {} is not in the graph", step),
+            OrderError::Repeated(step) => write!(f, "step {} is taken twice", step),
+            OrderError::Dependency { step, requires } => {
+                write!(
+                    f,
+                    "step {} is taken before {}, which it requires",
+                    step, requires
+                )
+            }
+            OrderError::Missing(steps) => write!(f, "steps {} are never taken", steps.join(", ")),
+        }
+    }
+}
+
+impl Error for OrderError {}
+
+/// Steps by index in alphabetical order, with their links
+#[derive(Debug)]
+struct IndexedGraph {
+    names: Vec<String>,
+    dependencies: Vec<Vec<usize>>,
+    dependants: Vec<Vec<usize>>,
+}
+
+impl IndexedGraph {
+    fn new(graph: &InstructionGraph) -> Self {
+        let names = graph.nodes();
+        let index = names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| (name.as_str(), i))
+            .collect::<HashMap<_, _>>();
+
+        let mut dependencies = vec![Vec::new(); names.len()];
+        let mut dependants = vec![Vec::new(); names.len()];
+        for (from, to) in graph.edges() {
+            dependencies[index[to]].push(index[from]);
+            dependants[index[from]].push(index[to]);
+        }
+
+        Self {
+            names,
+            dependencies,
+            dependants,
+        }
+    }
+}
+
+/// Number of valid orders of the steps, counted by DP over the sets of steps taken
+/// (ones closed under dependencies)
+pub(crate) fn count_orders(graph: &InstructionGraph) -> Result<u128, GraphError> {
+    graph.check_acyclic()?;
+
+    let graph = IndexedGraph::new(graph);
+    let n = graph.names.len();
+    if n > MAX_COUNTED_STEPS {
+        return Err(GraphError::TooManySteps(n));
+    }
+
+    let dependency_masks = graph
+        .dependencies
+        .iter()
+        .map(|deps| deps.iter().fold(0u64, |mask, dep| mask | 1 << dep))
+        .collect::<Vec<_>>();
+
+    fn count(taken: u64, full: u64, deps: &[u64], memo: &mut HashMap<u64, u128>) -> Option<u128> {
+        if taken == full {
+            return Some(1);
+        }
+        if let Some(&known) = memo.get(&taken) {
+            return Some(known);
+        }
+        if memo.len() >= MAX_COUNTED_STATES {
+            return None;
+        }
+
+        let mut total = 0;
+        for i in (0..deps.len()).filter(|&i| taken & (1 << i) == 0 && deps[i] & !taken == 0) {
+            total += count(taken | 1 << i, full, deps, memo)?;
+        }
+        memo.insert(taken, total);
+
+        Some(total)
+    }
+
+    let full = (1u64 << n) - 1;
+
+    count(0, full, &dependency_masks, &mut HashMap::new())
+        .ok_or(GraphError::TooManyStepSets(MAX_COUNTED_STATES))
+}
+
+/// Lazily yields every valid order of the steps, in lexicographic order
+#[derive(Debug)]
+pub(crate) struct TopologicalOrders {
+    graph: IndexedGraph,
+    pending: Vec<usize>,
+    taken: Vec<bool>,
+    order: Vec<usize>,
+    /// Next step index to try at every depth of the current order
+    cursor: Vec<usize>,
+    finished: bool,
+}
+
+impl TopologicalOrders {
+    pub(crate) fn new(graph: &InstructionGraph) -> Result<Self, GraphError> {
+        graph.check_acyclic()?;
+
+        let graph = IndexedGraph::new(graph);
+        let pending = graph.dependencies.iter().map(Vec::len).collect();
+        let n = graph.names.len();
+
+        Ok(Self {
+            graph,
+            pending,
+            taken: vec![false; n],
+            order: Vec::with_capacity(n),
+            cursor: vec![0],
+            finished: false,
+        })
+    }
+
+    fn take(&mut self, step: usize) {
+        self.taken[step] = true;
+        self.order.push(step);
+
+        for &next in &self.graph.dependants[step] {
+            self.pending[next] -= 1;
+        }
+    }
+
+    fn untake(&mut self) {
+        let step = self.order.pop().unwrap();
+        self.taken[step] = false;
+
+        for &next in &self.graph.dependants[step] {
+            self.pending[next] += 1;
+        }
+    }
+
+    fn names(&self) -> Vec<String> {
+        self.order
+            .iter()
+            .map(|&step| self.graph.names[step].clone())
+            .collect()
+    }
+}
+
+impl Iterator for TopologicalOrders {
+    type Item = Vec<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let n = self.graph.names.len();
+
+        if n == 0 && !self.finished {
+            self.finished = true;
+
+            return Some(Vec::new());
+        }
+
+        while !self.finished {
+            let depth = self.order.len();
+            let start = self.cursor[depth];
+            let candidate = (start..n).find(|&step| !self.taken[step] && self.pending[step] == 0);
+
+            match candidate {
+                Some(step) => {
+                    self.cursor[depth] = step + 1;
+                    self.take(step);
+
+                    if self.order.len() == n {
+                        let order = self.names();
+                        self.untake();
+
+                        return Some(order);
+                    }
+
+                    self.cursor.push(0);
+                }
+                None if depth == 0 => self.finished = true,
+                None => {
+                    self.cursor.pop();
+                    self.untake();
+                }
+            }
+        }
+
+        None
+    }
+}
+
+/// Check, that `order` takes every step exactly once and after all of its dependencies
+pub(crate) fn check_order<S: AsRef<str>>(
+    graph: &InstructionGraph,
+    order: &[S],
+) -> Result<(), OrderError> {
+    let graph = IndexedGraph::new(graph);
+    let index = graph
+        .names
+        .iter()
+        .enumerate()
+        .map(|(i, name)| (name.as_str(), i))
+        .collect::<HashMap<_, _>>();
+
+    let mut taken = vec![false; graph.names.len()];
+
+    for step in order {
+        let step = step.as_ref();
+        let i = *index
+            .get(step)
+            .ok_or_else(|| OrderError::UnknownStep(step.to_owned()))?;
+
+        if taken[i] {
+            return Err(OrderError::Repeated(step.to_owned()));
+        }
+
+        if let Some(&dep) = graph.dependencies[i].iter().find(|&&dep| !taken[dep]) {
+            return Err(OrderError::Dependency {
+                step: step.to_owned(),
+                requires: graph.names[dep].clone(),
+            });
+        }
+
+        taken[i] = true;
+    }
+
+    let missing = graph
+        .names
+        .iter()
+        .zip(&taken)
+        .filter(|(_, &taken)| !taken)
+        .map(|(name, _)| name.clone())
+        .collect::<Vec<_>>();
+
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(OrderError::Missing(missing))
+    }
+}
+
+/// Split an order string: by commas and whitespace if there are any,
+/// otherwise every character is a step, as in the puzzle answer
+pub(crate) fn split_order(order: &str) -> Vec<String> {
+    let is_separator = |c: char| c == ',' || c.is_whitespace();
+
+    if order.contains(is_separator) {
+        order
+            .split(is_separator)
+            .filter(|step| !step.is_empty())
+            .map(str::to_owned)
+            .collect()
+    } else {
+        order.chars().map(String::from).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::fixtures::example_graph;
+
+    fn joined(orders: impl Iterator<Item = Vec<String>>) -> Vec<String> {
+        orders.map(|order| order.concat()).collect()
+    }
+
+    #[test]
+    fn test_enumerate_orders() {
+        let orders = joined(TopologicalOrders::new(&example_graph()).unwrap());
+
+        assert_eq!(
+            orders,
+            vec!["CABDFE", "CABFDE", "CADBFE", "CADFBE", "CAFBDE", "CAFDBE", "CFABDE", "CFADBE",]
+        );
+        assert_eq!(count_orders(&example_graph()), Ok(orders.len() as u128));
+    }
+
+    #[test]
+    fn test_count_orders() {
+        // chain has the only order
+        let mut graph = InstructionGraph::new();
+        graph.add_link("A", "B");
+        graph.add_link("B", "C");
+        assert_eq!(count_orders(&graph), Ok(1));
+
+        // independent pairs: 4! / (2! * 2!)
+        let mut graph = InstructionGraph::new();
+        graph.add_link("A", "B");
+        graph.add_link("C", "D");
+        assert_eq!(count_orders(&graph), Ok(6));
+        assert_eq!(TopologicalOrders::new(&graph).unwrap().count(), 6);
+
+        assert_eq!(count_orders(&InstructionGraph::new()), Ok(1));
+        assert_eq!(
+            TopologicalOrders::new(&InstructionGraph::new())
+                .unwrap()
+                .collect::<Vec<_>>(),
+            vec![Vec::<String>::new()]
+        );
+
+        let mut graph = InstructionGraph::new();
+        graph.add_link("A", "B");
+        graph.add_link("B", "A");
+        assert!(matches!(count_orders(&graph), Err(GraphError::Cycle(_))));
+    }
+
+    #[test]
+    fn test_count_orders_too_large() {
+        let mut graph = InstructionGraph::new();
+
+        for i in 0..17 {
+            graph.add_link(&format!("a{:02}", i), &format!("b{:02}", i));
+        }
+
+        assert_eq!(count_orders(&graph), Err(GraphError::TooManySteps(34)));
+    }
+
+    #[test]
+    fn test_count_orders_too_many_states() {
+        // every subset of the leaves can be taken after the root
+        let mut graph = InstructionGraph::new();
+
+        for i in 0..31 {
+            graph.add_link("root", &format!("leaf{:02}", i));
+        }
+
+        assert_eq!(
+            count_orders(&graph),
+            Err(GraphError::TooManyStepSets(MAX_COUNTED_STATES))
+        );
+
+        // long chain has a single set of every size
+        let mut graph = InstructionGraph::new();
+
+        for i in 0..31 {
+            graph.add_link(&format!("step{:02}", i), &format!("step{:02}", i + 1));
+        }
+
+        assert_eq!(count_orders(&graph), Ok(1));
+    }
+
+    #[test]
+    fn test_count_orders_wide() {
+        // 8 independent pairs: 16! / 2^8
+        let mut graph = InstructionGraph::new();
+
+        for i in 0..8 {
+            graph.add_link(&format!("a{:02}", i), &format!("b{:02}", i));
+        }
+
+        let expected = (1..=16u128).product::<u128>() >> 8;
+        assert_eq!(count_orders(&graph), Ok(expected));
+    }
+
+    #[test]
+    fn test_enumeration_is_lazy() {
+        let mut graph = InstructionGraph::new();
+
+        for i in 0..30 {
+            graph.add_link(&format!("a{:02}", i), &format!("b{:02}", i));
+        }
+
+        let first = TopologicalOrders::new(&graph).unwrap().next().unwrap();
+        assert_eq!(first[..3], ["a00", "a01", "a02"]);
+    }
+
+    #[test]
+    fn test_check_order() {
+        let graph = example_graph();
+
+        assert_eq!(check_order(&graph, &split_order("CABDFE")), Ok(()));
+        assert_eq!(
+            check_order(&graph, &split_order("C, F, A, D, B, E")),
+            Ok(())
+        );
+        assert_eq!(
+            check_order(&graph, &split_order("CABEDF")),
+            Err(OrderError::Dependency {
+                step: "E".to_owned(),
+                requires: "D".to_owned()
+            })
+        );
+        assert_eq!(
+            check_order(&graph, &split_order("CABDFX")),
+            Err(OrderError::UnknownStep("X".to_owned()))
+        );
+        assert_eq!(
+            check_order(&graph, &split_order("CAA")),
+            Err(OrderError::Repeated("A".to_owned()))
+        );
+        assert_eq!(
+            check_order(&graph, &split_order("CAB")),
+            Err(OrderError::Missing(vec![
+                "D".to_owned(),
+                "E".to_owned(),
+                "F".to_owned()
+            ]))
+        );
+        assert_eq!(
+            check_order(&graph, &split_order("CABEDF"))
+                .unwrap_err()
+                .to_string(),
+            "step E is taken before D, which it requires"
+        );
+    }
+}
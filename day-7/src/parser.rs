@@ -1,17 +1,17 @@
-use std::path::Path;
+use std::{error::Error, path::Path};
 
 use advent_utils::parse_file;
 
 use crate::graph::InstructionGraph;
 use crate::instruction::OperationOrder;
 
-pub(crate) fn parse_graph<P: AsRef<Path>>(p: P) -> InstructionGraph {
-    parse_file(p).unwrap().into_iter().fold(
+pub(crate) fn parse_graph<P: AsRef<Path>>(p: P) -> Result<InstructionGraph, Box<dyn Error>> {
+    Ok(parse_file(p)?.into_iter().fold(
         InstructionGraph::new(),
         |mut graph, order: OperationOrder| {
-            graph.add_link(order.first, order.second);
+            graph.add_link(&order.first, &order.second);
 
             graph
         },
-    )
+    ))
 }
@@ -0,0 +1,215 @@
+use std::fmt::Write;
+
+use crate::traversers::ScheduledStep;
+
+/// Step names joined into a single string: single-letter steps are written as a word,
+/// like the puzzle does, longer names are separated with commas
+pub(crate) fn join_steps<S: AsRef<str>>(steps: &[S]) -> String {
+    let separator = if steps.iter().all(|step| step.as_ref().chars().count() == 1) {
+        ""
+    } else {
+        ", "
+    };
+
+    steps
+        .iter()
+        .map(AsRef::as_ref)
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+fn finish_time(schedule: &[ScheduledStep]) -> i32 {
+    schedule.iter().map(|step| step.finish).max().unwrap_or(0)
+}
+
+fn running_at(schedule: &[ScheduledStep], worker: usize, second: i32) -> Option<&ScheduledStep> {
+    schedule
+        .iter()
+        .find(|step| step.workers.contains(&worker) && step.start <= second && second < step.finish)
+}
+
+/// Puzzle-style table: what each worker does at every second and which steps are done
+pub(crate) fn render_table(schedule: &[ScheduledStep], n_workers: usize) -> String {
+    let headers = (1..=n_workers)
+        .map(|worker| format!("Worker {}", worker))
+        .collect::<Vec<_>>();
+    let widths = headers
+        .iter()
+        .map(|header| {
+            schedule
+                .iter()
+                .map(|step| step.name.len())
+                .fold(header.len(), usize::max)
+        })
+        .collect::<Vec<_>>();
+
+    let mut table = String::from("Second");
+    for (header, width) in headers.iter().zip(&widths) {
+        write!(table, "   {:^width$}", header, width = width).unwrap();
+    }
+    table.push_str("   Done\n");
+
+    let mut done = schedule.to_vec();
+    done.sort_by(|a, b| (a.finish, &a.name).cmp(&(b.finish, &b.name)));
+
+    for second in 0..=finish_time(schedule) {
+        write!(table, "{:>4}  ", second).unwrap();
+
+        for (worker, width) in widths.iter().enumerate() {
+            let cell = running_at(schedule, worker, second).map_or(".", |step| &step.name);
+
+            write!(table, "   {:^width$}", cell, width = width).unwrap();
+        }
+
+        let finished = done
+            .iter()
+            .take_while(|step| step.finish <= second)
+            .map(|step| step.name.as_str())
+            .collect::<Vec<_>>();
+
+        writeln!(table, "   {}", join_steps(&finished)).unwrap();
+    }
+
+    table
+}
+
+/// ASCII Gantt chart, one row per worker, one character per `scale` seconds
+pub(crate) fn render_gantt(schedule: &[ScheduledStep], n_workers: usize, scale: i32) -> String {
+    let scale = scale.max(1);
+    let total = finish_time(schedule);
+    let cells = (total + scale - 1) / scale;
+
+    let mut chart = String::new();
+
+    for worker in 0..n_workers {
+        write!(chart, "Worker {} |", worker + 1).unwrap();
+
+        for cell in 0..cells {
+            let second = cell * scale;
+            let symbol = running_at(schedule, worker, second).map_or('.', |step| {
+                let offset = ((second - step.start) / scale) as usize;
+
+                step.name.chars().cycle().nth(offset).unwrap_or('#')
+            });
+
+            chart.push(symbol);
+        }
+
+        chart.push_str("|\n");
+    }
+
+    chart
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct WorkerUsage {
+    pub busy: i32,
+    pub total: i32,
+}
+
+impl WorkerUsage {
+    pub(crate) fn ratio(&self) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            f64::from(self.busy) / f64::from(self.total)
+        }
+    }
+}
+
+/// Busy time of each worker over the whole schedule
+pub(crate) fn utilisation(schedule: &[ScheduledStep], n_workers: usize) -> Vec<WorkerUsage> {
+    let total = finish_time(schedule);
+
+    (0..n_workers)
+        .map(|worker| WorkerUsage {
+            busy: schedule
+                .iter()
+                .filter(|step| step.workers.contains(&worker))
+                .map(|step| step.finish - step.start)
+                .sum(),
+            total,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::fixtures::{example_graph, example_schedule};
+
+    #[test]
+    fn test_join_steps() {
+        assert_eq!(join_steps(&["C", "A", "B"]), "CAB");
+        assert_eq!(join_steps(&["compile", "link"]), "compile, link");
+        assert_eq!(join_steps::<&str>(&[]), "");
+    }
+
+    #[test]
+    fn test_render_table() {
+        let table = render_table(&example_schedule(&example_graph()), 2);
+        let lines = table.lines().map(str::trim_end).collect::<Vec<_>>();
+
+        assert_eq!(lines.len(), 1 + 16);
+        assert_eq!(lines[0], "Second   Worker 1   Worker 2   Done");
+        assert_eq!(lines[1], "   0        C          .");
+        assert_eq!(lines[4], "   3        F          A       C");
+        assert_eq!(lines[10], "   9        .          D       CABF");
+        assert_eq!(lines[16], "  15        .          .       CABFDE");
+    }
+
+    #[test]
+    fn test_render_table_long_names() {
+        let schedule = vec![ScheduledStep {
+            name: "compile-core".to_owned(),
+            workers: vec![0],
+            start: 0,
+            finish: 1,
+        }];
+        let table = render_table(&schedule, 1);
+        let lines = table.lines().map(str::trim_end).collect::<Vec<_>>();
+
+        assert_eq!(
+            lines,
+            vec![
+                "Second     Worker 1     Done",
+                "   0     compile-core",
+                "   1          .         compile-core",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_gantt() {
+        let schedule = example_schedule(&example_graph());
+
+        assert_eq!(
+            render_gantt(&schedule, 2, 1),
+            "Worker 1 |CCCFFFFFF.EEEEE|\nWorker 2 |...ABBDDDD.....|\n"
+        );
+        assert_eq!(
+            render_gantt(&schedule, 2, 2),
+            "Worker 1 |CCFFFEEE|\nWorker 2 |..BDD...|\n"
+        );
+        assert_eq!(render_gantt(&[], 1, 1), "Worker 1 ||\n");
+    }
+
+    #[test]
+    fn test_utilisation() {
+        let usage = utilisation(&example_schedule(&example_graph()), 2);
+
+        assert_eq!(
+            usage,
+            vec![
+                WorkerUsage {
+                    busy: 14,
+                    total: 15,
+                },
+                WorkerUsage { busy: 7, total: 15 },
+            ]
+        );
+        assert!((usage[1].ratio() - 7.0 / 15.0).abs() < 1e-9);
+        assert_eq!(utilisation(&[], 1)[0].ratio(), 0.0);
+    }
+}
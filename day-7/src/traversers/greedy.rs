@@ -0,0 +1,37 @@
+use std::{cmp::Reverse, collections::BinaryHeap, num::NonZeroUsize};
+
+use crate::{costs::CostTable, graph::InstructionGraph, workers::Pool};
+
+/// Former pooled traversal, kept as a reference for tests: pops the step with the smallest
+/// ready time and hands it to the earliest free worker. Successor's ready time is the finish
+/// of whichever dependency was popped last, and steps queued earlier win over ones,
+/// that become available before the worker gets free.
+pub(super) fn greedy_order(
+    graph: &InstructionGraph,
+    costs: &CostTable,
+    n_workers: NonZeroUsize,
+) -> (Vec<(i32, String)>, i32) {
+    let mut traversal = graph.traversal();
+    let mut pool = Pool::new(n_workers);
+    let mut tasks = graph
+        .get_starting_nodes()
+        .map(|node| Reverse((0, node)))
+        .collect::<BinaryHeap<_>>();
+    let mut order = Vec::new();
+
+    while let Some(Reverse((ready_at, node))) = tasks.pop() {
+        let duration = costs.cost(&node).unwrap();
+        let (_, finish_time) = pool.assign_work(ready_at, duration);
+
+        tasks.extend(
+            traversal
+                .visit_node(&node)
+                .unwrap()
+                .into_iter()
+                .map(|node| Reverse((finish_time, node))),
+        );
+        order.push((finish_time - duration, node));
+    }
+
+    (order, pool.free_at())
+}
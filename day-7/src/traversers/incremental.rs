@@ -0,0 +1,309 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use crate::{
+    costs::CostTable,
+    graph::{GraphError, InstructionGraph},
+    workers::{Pool, StepRequirement},
+};
+
+use super::{PooledTraverser, ScheduledStep};
+
+/// Change to the graph or to the step costs
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "change", rename_all = "lowercase")]
+pub(crate) enum ScheduleChange {
+    Duration { step: String, cost: i32 },
+    Add { from: String, to: String },
+    Remove { from: String, to: String },
+}
+
+/// Step, that changed its start, finish or workers
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct MovedStep {
+    pub before: ScheduledStep,
+    pub after: ScheduledStep,
+}
+
+/// Pooled schedule, that is kept up to date with the changes: only the part after
+/// the first instant a change can affect is simulated again
+#[derive(Debug)]
+pub(crate) struct Rescheduler {
+    graph: InstructionGraph,
+    costs: CostTable,
+    pool: Pool,
+    requirements: HashMap<String, StepRequirement>,
+    schedule: Vec<ScheduledStep>,
+}
+
+impl Rescheduler {
+    pub(crate) fn new(
+        graph: InstructionGraph,
+        costs: CostTable,
+        pool: Pool,
+        requirements: HashMap<String, StepRequirement>,
+    ) -> Result<Self, GraphError> {
+        let schedule =
+            PooledTraverser::with_pool(&graph, costs.clone(), pool.clone(), requirements.clone())?
+                .schedule()?;
+
+        Ok(Self {
+            graph,
+            costs,
+            pool,
+            requirements,
+            schedule,
+        })
+    }
+
+    pub(crate) fn finish_time(&self) -> i32 {
+        self.schedule
+            .iter()
+            .map(|step| step.finish)
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn scheduled(&self, step: &str) -> Result<&ScheduledStep, GraphError> {
+        self.schedule
+            .iter()
+            .find(|scheduled| scheduled.name == step)
+            .ok_or_else(|| GraphError::MissingNode(step.to_owned()))
+    }
+
+    fn check_node(&self, step: &str) -> Result<(), GraphError> {
+        if self.graph.contains(step) {
+            Ok(())
+        } else {
+            Err(GraphError::MissingNode(step.to_owned()))
+        }
+    }
+
+    /// Simulate again from the instant `at`, returning the steps, that moved
+    fn replay_from(&mut self, at: i32) -> Result<Vec<MovedStep>, GraphError> {
+        let schedule = PooledTraverser::with_pool(
+            &self.graph,
+            self.costs.clone(),
+            self.pool.clone(),
+            self.requirements.clone(),
+        )?
+        .resume(&self.schedule, at)?
+        .schedule()?;
+
+        let before = self
+            .schedule
+            .iter()
+            .map(|step| (step.name.as_str(), step))
+            .collect::<HashMap<_, _>>();
+
+        let moved = schedule
+            .iter()
+            .filter(|step| before[step.name.as_str()] != *step)
+            .map(|step| MovedStep {
+                before: before[step.name.as_str()].clone(),
+                after: step.clone(),
+            })
+            .collect();
+
+        self.schedule = schedule;
+
+        Ok(moved)
+    }
+
+    /// Nothing before the step's start depends on its duration
+    pub(crate) fn set_duration(
+        &mut self,
+        step: &str,
+        duration: i32,
+    ) -> Result<Vec<MovedStep>, GraphError> {
+        let at = self.scheduled(step)?.start;
+        if duration < 0 {
+            return Err(GraphError::NegativeCost(step.to_owned()));
+        }
+
+        let previous = self.costs.cost(step);
+
+        self.costs.set(step, duration);
+
+        self.replay_from(at).inspect_err(|_| {
+            if let Some(previous) = previous {
+                self.costs.set(step, previous);
+            }
+        })
+    }
+
+    /// New dependency can only hold `to` back, so nothing before its start is affected
+    pub(crate) fn add_dependency(
+        &mut self,
+        from: &str,
+        to: &str,
+    ) -> Result<Vec<MovedStep>, GraphError> {
+        self.check_node(from)?;
+        let at = self.scheduled(to)?.start;
+
+        self.graph.add_link(from, to);
+
+        self.replay_from(at).inspect_err(|_| {
+            self.graph.remove_link(from, to);
+        })
+    }
+
+    /// Without the dependency `to` can start as soon as the rest of its dependencies are done
+    pub(crate) fn remove_dependency(
+        &mut self,
+        from: &str,
+        to: &str,
+    ) -> Result<Vec<MovedStep>, GraphError> {
+        let dependencies = self.graph.dependencies(to);
+        if !dependencies.iter().any(|dependency| dependency == from) {
+            return Err(GraphError::MissingLink(from.to_owned(), to.to_owned()));
+        }
+
+        let mut at = 0;
+        for dependency in dependencies.iter().filter(|dependency| *dependency != from) {
+            at = at.max(self.scheduled(dependency)?.finish);
+        }
+
+        self.graph.remove_link(from, to);
+
+        self.replay_from(at).inspect_err(|_| {
+            self.graph.add_link(from, to);
+        })
+    }
+
+    pub(crate) fn apply(&mut self, change: &ScheduleChange) -> Result<Vec<MovedStep>, GraphError> {
+        match change {
+            ScheduleChange::Duration { step, cost } => self.set_duration(step, *cost),
+            ScheduleChange::Add { from, to } => self.add_dependency(from, to),
+            ScheduleChange::Remove { from, to } => self.remove_dependency(from, to),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::num::NonZeroUsize;
+
+    use crate::fixtures::example_graph;
+
+    fn rescheduler(graph: InstructionGraph) -> Rescheduler {
+        Rescheduler::new(
+            graph,
+            CostTable::letters(0),
+            Pool::new(NonZeroUsize::new(2).unwrap()),
+            HashMap::new(),
+        )
+        .unwrap()
+    }
+
+    /// Schedule of the same graph and costs, computed from scratch
+    fn full_schedule(rescheduler: &Rescheduler) -> Vec<ScheduledStep> {
+        PooledTraverser::with_pool(
+            &rescheduler.graph,
+            rescheduler.costs.clone(),
+            rescheduler.pool.clone(),
+            rescheduler.requirements.clone(),
+        )
+        .and_then(PooledTraverser::schedule)
+        .unwrap()
+    }
+
+    fn moved_names(moved: &[MovedStep]) -> Vec<&str> {
+        moved.iter().map(|step| step.after.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_set_duration() {
+        let mut rescheduler = rescheduler(example_graph());
+        assert_eq!(rescheduler.finish_time(), 15);
+
+        // F: 3..9 -> 3..5, then its worker picks D, which has been waiting since 4
+        let moved = rescheduler.set_duration("F", 2).unwrap();
+
+        assert_eq!(moved_names(&moved), vec!["F", "D", "E"]);
+        assert_eq!((moved[1].before.start, moved[1].after.start), (6, 5));
+        assert_eq!(rescheduler.finish_time(), 14);
+        assert_eq!(rescheduler.schedule, full_schedule(&rescheduler));
+
+        // E: 9..14 -> 9..10, nothing else depends on it
+        let moved = rescheduler.set_duration("E", 1).unwrap();
+
+        assert_eq!(moved_names(&moved), vec!["E"]);
+        assert_eq!(rescheduler.finish_time(), 10);
+        assert_eq!(rescheduler.schedule, full_schedule(&rescheduler));
+    }
+
+    #[test]
+    fn test_change_dependencies() {
+        let mut rescheduler = rescheduler(example_graph());
+
+        // B has to wait for F now: 9..11
+        let moved = rescheduler.add_dependency("F", "B").unwrap();
+
+        assert!(moved_names(&moved).contains(&"B"));
+        assert_eq!(rescheduler.schedule, full_schedule(&rescheduler));
+
+        let moved = rescheduler.remove_dependency("F", "B").unwrap();
+
+        assert!(moved_names(&moved).contains(&"B"));
+        assert_eq!(rescheduler.schedule, full_schedule(&rescheduler));
+        assert_eq!(rescheduler.finish_time(), 15);
+
+        // D doesn't need A anymore, so it starts along with A
+        rescheduler.remove_dependency("A", "D").unwrap();
+        assert_eq!(rescheduler.schedule, full_schedule(&rescheduler));
+    }
+
+    #[test]
+    fn test_change_errors() {
+        let mut rescheduler = rescheduler(example_graph());
+
+        assert!(matches!(
+            rescheduler.add_dependency("E", "C"),
+            Err(GraphError::Cycle(_))
+        ));
+        // failed change is rolled back
+        assert_eq!(rescheduler.graph.dependencies("C"), &[] as &[String]);
+
+        assert_eq!(
+            rescheduler.remove_dependency("C", "E"),
+            Err(GraphError::MissingLink("C".to_owned(), "E".to_owned()))
+        );
+        assert_eq!(
+            rescheduler.set_duration("X", 1),
+            Err(GraphError::MissingNode("X".to_owned()))
+        );
+        assert_eq!(
+            rescheduler.apply(&ScheduleChange::Duration {
+                step: "F".to_owned(),
+                cost: -3
+            }),
+            Err(GraphError::NegativeCost("F".to_owned()))
+        );
+        assert_eq!(rescheduler.costs.cost("F"), Some(6));
+        assert_eq!(
+            rescheduler.apply(&ScheduleChange::Add {
+                from: "X".to_owned(),
+                to: "C".to_owned()
+            }),
+            Err(GraphError::MissingNode("X".to_owned()))
+        );
+        assert_eq!(rescheduler.finish_time(), 15);
+
+        // link stays when the rest of the dependencies can't be looked up
+        rescheduler
+            .schedule
+            .retain(|scheduled| scheduled.name != "B");
+        assert_eq!(
+            rescheduler.remove_dependency("D", "E"),
+            Err(GraphError::MissingNode("B".to_owned()))
+        );
+        assert!(rescheduler
+            .graph
+            .dependencies("E")
+            .contains(&"D".to_owned()));
+    }
+}
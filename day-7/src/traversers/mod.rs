@@ -1,5 +1,9 @@
+#[cfg(test)]
+mod greedy;
+mod incremental;
 mod pooled;
 mod simple;
 
-pub(crate) use self::pooled::PooledTraverser;
+pub(crate) use self::incremental::{Rescheduler, ScheduleChange};
+pub(crate) use self::pooled::{PooledTraverser, ScheduledStep};
 pub(crate) use self::simple::SimpleTraverser;
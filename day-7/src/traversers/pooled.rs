@@ -1,80 +1,231 @@
-use std::{cmp::Reverse, collections::BinaryHeap, num::NonZeroUsize};
-
-use crate::{graph::InstructionGraph, workers::Pool};
-
-const LETTER_A: i32 = 'A' as i32;
-
-/// Compute letter number (only uppercased ASCII)
-///
-/// ```
-/// assert_eq!(letter_number('A'), 1);
-/// assert_eq!(letter_number('Z'), 26);
-/// ```
-fn letter_number(letter: char) -> i32 {
-    debug_assert!(letter <= 'Z');
-    debug_assert!(letter >= 'A');
-    letter as i32 - LETTER_A + 1
+use std::{
+    cmp::Reverse,
+    collections::{BTreeSet, BinaryHeap, HashMap},
+    num::NonZeroUsize,
+};
+
+use crate::{
+    costs::CostTable,
+    graph::{GraphError, InstructionGraph, Traversal},
+    workers::{Pool, StepRequirement},
+};
+
+/// Step as it was actually executed by some workers
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ScheduledStep {
+    pub name: String,
+    /// Ids of the workers busy with the step, counted from zero
+    pub workers: Vec<usize>,
+    pub start: i32,
+    pub finish: i32,
 }
 
+/// Discrete-event simulation of the workers: the clock advances from one finish to the next,
+/// and at every instant available steps are handed to idle workers in alphabetical order;
+/// a step, which lacks idle capable workers, waits while the following ones may start
 #[derive(Debug)]
-pub(crate) struct PooledTraverser {
-    graph: InstructionGraph,
+pub(crate) struct PooledTraverser<'a> {
+    traversal: Traversal<'a>,
     pool: Pool,
-    delta: i32,
-    tasks: BinaryHeap<Reverse<(i32, char)>>,
+    costs: CostTable,
+    /// Requirement of every step in the graph
+    requirements: HashMap<String, StepRequirement>,
+    clock: i32,
+    /// Steps with all the dependencies done, waiting for a worker
+    available: BTreeSet<String>,
+    /// Steps being worked on, by finish time
+    running: BinaryHeap<Reverse<(i32, String)>>,
+    failed: bool,
+    schedule: Vec<ScheduledStep>,
 }
 
-impl PooledTraverser {
+impl<'a> PooledTraverser<'a> {
     pub(crate) fn new(
-        graph: InstructionGraph,
-        work_price_delta: i32,
+        graph: &'a InstructionGraph,
+        costs: CostTable,
         n_workers: NonZeroUsize,
-    ) -> Self {
-        let tasks = graph
-            .get_starting_nodes()
-            .map(|node| Reverse((0, node)))
-            .collect();
-
-        Self {
-            graph,
-            pool: Pool::new(n_workers),
-            delta: work_price_delta,
-            tasks,
+    ) -> Result<Self, GraphError> {
+        Self::with_pool(graph, costs, Pool::new(n_workers), HashMap::new())
+    }
+
+    /// Traverser for workers with capabilities; steps missing from `requirements`
+    /// need a single worker of any kind
+    pub(crate) fn with_pool(
+        graph: &'a InstructionGraph,
+        costs: CostTable,
+        pool: Pool,
+        mut requirements: HashMap<String, StepRequirement>,
+    ) -> Result<Self, GraphError> {
+        graph.check_acyclic()?;
+
+        for (step, requirement) in &requirements {
+            if !graph.contains(step) {
+                return Err(GraphError::MissingNode(step.clone()));
+            }
+
+            if requirement.workers == 0 {
+                return Err(GraphError::NoWorkersRequired(step.clone()));
+            }
         }
+
+        for step in graph.nodes() {
+            match costs.cost(&step) {
+                None => return Err(GraphError::MissingCost(step)),
+                Some(cost) if cost < 0 => return Err(GraphError::NegativeCost(step)),
+                Some(_) => {}
+            }
+
+            if !pool.can_perform(requirements.entry(step.clone()).or_default()) {
+                return Err(GraphError::NoCapableWorkers(step));
+            }
+        }
+
+        let available = graph.get_starting_nodes().collect();
+
+        Ok(Self {
+            traversal: graph.traversal(),
+            pool,
+            costs,
+            requirements,
+            clock: 0,
+            available,
+            running: BinaryHeap::new(),
+            failed: false,
+            schedule: Vec::new(),
+        })
+    }
+
+    /// Continue from the instant `at` of an earlier run: steps of `schedule`, that started
+    /// before it, are taken as they were, everything else is simulated anew
+    pub(crate) fn resume(
+        mut self,
+        schedule: &[ScheduledStep],
+        at: i32,
+    ) -> Result<Self, GraphError> {
+        let (kept, _): (Vec<_>, Vec<_>) = schedule.iter().partition(|step| step.start < at);
+
+        for step in &kept {
+            self.pool
+                .assign_to(&step.workers, step.start, step.finish - step.start);
+            self.schedule.push((*step).clone());
+
+            if step.finish <= at {
+                self.available
+                    .extend(self.traversal.visit_node(&step.name)?);
+            } else {
+                self.running.push(Reverse((step.finish, step.name.clone())));
+            }
+        }
+
+        for step in &kept {
+            self.available.remove(&step.name);
+        }
+        self.clock = at;
+
+        Ok(self)
     }
 
-    fn work_duration(&self, work: char) -> i32 {
-        letter_number(work) + self.delta
+    fn work_duration(&self, work: &str) -> i32 {
+        // every step is checked to have a cost on creation
+        self.costs.cost(work).unwrap()
     }
 
-    pub(crate) fn graph_finish_time(mut self) -> i32 {
-        for (start_at, node) in &mut self {
-            // TODO: replace with dbg! after Rust 1.32 release
-            println!("{} will start at {}", node, start_at);
+    pub(crate) fn graph_finish_time(mut self) -> Result<i32, GraphError> {
+        for step in &mut self {
+            step?;
         }
 
-        self.pool.free_at()
+        Ok(self.pool.free_at())
+    }
+
+    /// Run the traversal to the end, returning the steps in the order they were taken
+    pub(crate) fn schedule(mut self) -> Result<Vec<ScheduledStep>, GraphError> {
+        for step in &mut self {
+            step?;
+        }
+
+        Ok(self.schedule)
     }
 }
 
-impl Iterator for PooledTraverser {
-    type Item = (i32, char);
+impl<'a> PooledTraverser<'a> {
+    /// Start the alphabetically first available step, for which there are enough idle workers
+    fn start_next(&mut self) -> Option<(i32, String)> {
+        let (node, workers) = self.available.iter().find_map(|node| {
+            self.pool
+                .idle_workers(self.clock, &self.requirements[node])
+                .map(|workers| (node.clone(), workers))
+        })?;
+        self.available.remove(&node);
 
-    fn next(&mut self) -> Option<Self::Item> {
-        let to_return = self.tasks.pop().map(|reversed| reversed.0);
+        let duration = self.work_duration(&node);
+        let finish_time = self.pool.assign_to(&workers, self.clock, duration);
 
-        if let Some((start_at, node)) = to_return {
-            let finish_time = self.pool.take_work(start_at, self.work_duration(node));
+        self.schedule.push(ScheduledStep {
+            name: node.clone(),
+            workers,
+            start: self.clock,
+            finish: finish_time,
+        });
+        self.running.push(Reverse((finish_time, node.clone())));
 
-            self.tasks.extend(
-                self.graph
-                    .visit_node(node)
-                    .into_iter()
-                    .map(|node| Reverse((finish_time, node))),
-            );
+        Some((self.clock, node))
+    }
+
+    /// Move the clock to the next finish, completing every step, that finishes at that instant
+    fn advance(&mut self) -> Result<(), GraphError> {
+        let next_finish = match self.running.peek() {
+            Some(Reverse((finish_time, _))) => *finish_time,
+            None => return Ok(()),
+        };
+        self.clock = next_finish;
+
+        while let Some(Reverse((finish_time, _))) = self.running.peek() {
+            if *finish_time != next_finish {
+                break;
+            }
+
+            let Reverse((_, node)) = self.running.pop().unwrap();
+            self.available.extend(self.traversal.visit_node(&node)?);
         }
 
-        to_return
+        Ok(())
+    }
+}
+
+/// Yields steps with their start times; if it can't proceed while some steps
+/// are still pending, yields a single error instead
+impl<'a> Iterator for PooledTraverser<'a> {
+    type Item = Result<(i32, String), GraphError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        let result = loop {
+            if let Some(step) = self.start_next() {
+                break Ok(step);
+            }
+
+            if self.running.is_empty() {
+                let remaining = self.traversal.remaining_nodes();
+
+                if remaining.is_empty() {
+                    return None;
+                }
+
+                break Err(GraphError::Stuck(remaining));
+            }
+
+            if let Err(e) = self.advance() {
+                break Err(e);
+            }
+        };
+
+        self.failed = result.is_err();
+
+        Some(result)
     }
 }
 
@@ -82,48 +233,347 @@ impl Iterator for PooledTraverser {
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_letter_number() {
-        assert_eq!(letter_number('A'), 1);
-        assert_eq!(letter_number('Z'), 26);
-    }
+    use crate::{traversers::greedy::greedy_order, workers::WorkerClass};
 
     #[test]
     fn test_order() {
         let mut graph = InstructionGraph::new();
 
-        graph.add_link('C', 'F');
-        graph.add_link('C', 'B');
-        graph.add_link('B', 'G');
+        graph.add_link("C", "F");
+        graph.add_link("C", "B");
+        graph.add_link("B", "G");
 
         assert_eq!(
-            PooledTraverser::new(graph, 0, NonZeroUsize::new(2).unwrap()).collect::<Vec<_>>(),
-            vec![(0, 'C'), (3, 'B'), (3, 'F'), (5, 'G')]
+            PooledTraverser::new(&graph, CostTable::letters(0), NonZeroUsize::new(2).unwrap())
+                .unwrap()
+                .collect::<Result<Vec<_>, _>>(),
+            Ok(vec![
+                (0, "C".to_owned()),
+                (3, "B".to_owned()),
+                (3, "F".to_owned()),
+                (5, "G".to_owned()),
+            ])
         )
     }
 
+    #[test]
+    fn test_cycle() {
+        let mut graph = InstructionGraph::new();
+
+        graph.add_link("C", "F");
+        graph.add_link("F", "C");
+
+        assert_eq!(
+            PooledTraverser::new(&graph, CostTable::letters(0), NonZeroUsize::new(2).unwrap())
+                .unwrap_err(),
+            GraphError::Cycle(vec!["C".to_owned(), "F".to_owned()])
+        );
+    }
+
     #[test]
     fn test_graph_finish_time() {
         let mut graph = InstructionGraph::new();
 
-        graph.add_link('C', 'F');
-        graph.add_link('C', 'B');
-        graph.add_link('B', 'G');
+        graph.add_link("C", "F");
+        graph.add_link("C", "B");
+        graph.add_link("B", "G");
+
+        assert_eq!(
+            PooledTraverser::new(&graph, CostTable::letters(0), NonZeroUsize::new(2).unwrap())
+                .and_then(PooledTraverser::graph_finish_time),
+            Ok(12)
+        );
+
+        let mut graph = InstructionGraph::new();
+
+        graph.add_link("C", "F");
+        graph.add_link("C", "B");
+        graph.add_link("B", "G");
+
+        assert_eq!(
+            PooledTraverser::new(&graph, CostTable::letters(2), NonZeroUsize::new(2).unwrap())
+                .and_then(PooledTraverser::graph_finish_time),
+            Ok(18)
+        );
+    }
+
+    #[test]
+    fn test_schedule() {
+        let mut graph = InstructionGraph::new();
+
+        graph.add_link("C", "F");
+        graph.add_link("C", "B");
+        graph.add_link("B", "G");
+
+        let schedule =
+            PooledTraverser::new(&graph, CostTable::letters(0), NonZeroUsize::new(1).unwrap())
+                .and_then(PooledTraverser::schedule)
+                .unwrap();
+
+        // single worker runs the steps one after another
+        assert_eq!(
+            schedule
+                .iter()
+                .map(|step| (step.name.as_str(), step.workers[0], step.start, step.finish))
+                .collect::<Vec<_>>(),
+            vec![
+                ("C", 0, 0, 3),
+                ("B", 0, 3, 5),
+                ("F", 0, 5, 11),
+                ("G", 0, 11, 18)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cost_table() {
+        let mut graph = InstructionGraph::new();
+
+        graph.add_link("compile-core", "link");
+        graph.add_link("compile-ui", "link");
+
+        let mut costs = CostTable::new();
+        costs.set("compile-core", 30);
+        costs.set("compile-ui", 20);
+
+        assert_eq!(
+            PooledTraverser::new(&graph, costs.clone(), NonZeroUsize::new(2).unwrap()).unwrap_err(),
+            GraphError::MissingCost("link".to_owned())
+        );
 
+        // negative durations would move the clock backwards
+        costs.set("link", -5);
         assert_eq!(
-            PooledTraverser::new(graph, 0, NonZeroUsize::new(2).unwrap()).graph_finish_time(),
-            12
+            PooledTraverser::new(&graph, costs, NonZeroUsize::new(2).unwrap()).unwrap_err(),
+            GraphError::NegativeCost("link".to_owned())
         );
 
         let mut graph = InstructionGraph::new();
 
-        graph.add_link('C', 'F');
-        graph.add_link('C', 'B');
-        graph.add_link('B', 'G');
+        graph.add_link("compile-core", "link");
+        graph.add_link("compile-ui", "test-ui");
+
+        let mut costs = CostTable::new();
+        costs.set("compile-core", 30);
+        costs.set("compile-ui", 20);
+        costs.set("link", 5);
+        costs.set("test-ui", 15);
+
+        let traverser = PooledTraverser::new(&graph, costs, NonZeroUsize::new(2).unwrap()).unwrap();
+        assert_eq!(
+            traverser.collect::<Result<Vec<_>, _>>(),
+            Ok(vec![
+                (0, "compile-core".to_owned()),
+                (0, "compile-ui".to_owned()),
+                (20, "test-ui".to_owned()),
+                (30, "link".to_owned()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_reuse_graph() {
+        let mut graph = InstructionGraph::new();
+
+        graph.add_link("C", "F");
+        graph.add_link("C", "B");
+        graph.add_link("B", "G");
+
+        let finish_time = |n_workers| {
+            PooledTraverser::new(
+                &graph,
+                CostTable::letters(0),
+                NonZeroUsize::new(n_workers).unwrap(),
+            )
+            .and_then(PooledTraverser::graph_finish_time)
+        };
+
+        assert_eq!(finish_time(1), Ok(18));
+        assert_eq!(finish_time(2), Ok(12));
+        assert_eq!(finish_time(1), Ok(18));
+        assert_eq!(graph.nodes().len(), 4);
+    }
+
+    fn build_graph() -> (InstructionGraph, CostTable) {
+        let mut graph = InstructionGraph::new();
+
+        graph.add_link("build", "sign");
+        graph.add_link("build", "package");
+
+        let mut costs = CostTable::new();
+        costs.set("build", 5);
+        costs.set("sign", 3);
+        costs.set("package", 4);
+
+        (graph, costs)
+    }
+
+    fn requirement(tags: &[&str], workers: usize) -> StepRequirement {
+        StepRequirement {
+            tags: tags.iter().map(|tag| (*tag).to_owned()).collect(),
+            workers,
+        }
+    }
+
+    fn build_pool() -> Pool {
+        Pool::from_classes(&[
+            WorkerClass {
+                count: 1,
+                tags: requirement(&["linux"], 1).tags,
+            },
+            WorkerClass {
+                count: 1,
+                tags: requirement(&["signer"], 1).tags,
+            },
+        ])
+        .unwrap()
+    }
+
+    #[test]
+    fn test_worker_requirements() {
+        let (graph, costs) = build_graph();
+
+        let mut requirements = HashMap::new();
+        requirements.insert("build".to_owned(), requirement(&["linux"], 1));
+        requirements.insert("sign".to_owned(), requirement(&["signer"], 1));
+        requirements.insert("package".to_owned(), requirement(&[], 2));
+
+        let schedule = PooledTraverser::with_pool(&graph, costs, build_pool(), requirements)
+            .and_then(PooledTraverser::schedule)
+            .unwrap();
+
+        // packaging takes both workers, so signing waits for it
+        assert_eq!(
+            schedule
+                .iter()
+                .map(|step| (
+                    step.name.as_str(),
+                    &step.workers[..],
+                    step.start,
+                    step.finish
+                ))
+                .collect::<Vec<_>>(),
+            vec![
+                ("build", &[0][..], 0, 5),
+                ("package", &[1, 0][..], 5, 9),
+                ("sign", &[1][..], 9, 12),
+            ]
+        );
+    }
 
+    #[test]
+    fn test_no_capable_workers() {
+        for (step, requirement) in &[
+            ("sign", requirement(&["signer"], 2)),
+            ("build", requirement(&["gpu"], 1)),
+        ] {
+            let (graph, costs) = build_graph();
+
+            let mut requirements = HashMap::new();
+            requirements.insert((*step).to_owned(), requirement.clone());
+
+            assert_eq!(
+                PooledTraverser::with_pool(&graph, costs, build_pool(), requirements).unwrap_err(),
+                GraphError::NoCapableWorkers((*step).to_owned())
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_requirements() {
+        for (step, requirement, error) in &[
+            (
+                "sign",
+                requirement(&[], 0),
+                GraphError::NoWorkersRequired("sign".to_owned()),
+            ),
+            (
+                "deploy",
+                requirement(&["linux"], 1),
+                GraphError::MissingNode("deploy".to_owned()),
+            ),
+        ] {
+            let (graph, costs) = build_graph();
+
+            let mut requirements = HashMap::new();
+            requirements.insert((*step).to_owned(), requirement.clone());
+
+            assert_eq!(
+                PooledTraverser::with_pool(&graph, costs, build_pool(), requirements).unwrap_err(),
+                *error
+            );
+        }
+    }
+
+    fn simulated_order(
+        graph: &InstructionGraph,
+        costs: CostTable,
+        n_workers: usize,
+    ) -> (Vec<(i32, String)>, i32) {
+        let mut traverser =
+            PooledTraverser::new(graph, costs, NonZeroUsize::new(n_workers).unwrap()).unwrap();
+        let order = (&mut traverser).collect::<Result<Vec<_>, _>>().unwrap();
+
+        (order, traverser.pool.free_at())
+    }
+
+    fn steps(steps: &[(i32, &str)]) -> Vec<(i32, String)> {
+        steps
+            .iter()
+            .map(|(start, name)| (*start, (*name).to_owned()))
+            .collect()
+    }
+
+    #[test]
+    fn test_waits_for_all_dependencies() {
+        let mut graph = InstructionGraph::new();
+
+        graph.add_link("compile-core", "link");
+        graph.add_link("compile-ui", "link");
+
+        let mut costs = CostTable::new();
+        costs.set("compile-core", 30);
+        costs.set("compile-ui", 20);
+        costs.set("link", 5);
+
+        // greedy version takes the ready time from whichever dependency was visited last,
+        // so "link" starts before "compile-core" is done
+        assert_eq!(
+            greedy_order(&graph, &costs, NonZeroUsize::new(2).unwrap()),
+            (
+                steps(&[(0, "compile-core"), (0, "compile-ui"), (20, "link")]),
+                30
+            )
+        );
+        assert_eq!(
+            simulated_order(&graph, costs, 2),
+            (
+                steps(&[(0, "compile-core"), (0, "compile-ui"), (30, "link")]),
+                35
+            )
+        );
+    }
+
+    #[test]
+    fn test_alphabetical_pick_at_instant() {
+        let mut graph = InstructionGraph::new();
+
+        graph.add_link("A", "B");
+        graph.add_link("C", "D");
+
+        // when A is done at 1, both B and C are available, so an idle worker takes B;
+        // greedy version has queued C back at 0 and takes it first
+        assert_eq!(
+            greedy_order(
+                &graph,
+                &CostTable::letters(0),
+                NonZeroUsize::new(1).unwrap()
+            ),
+            (steps(&[(0, "A"), (1, "C"), (4, "B"), (6, "D")]), 10)
+        );
         assert_eq!(
-            PooledTraverser::new(graph, 2, NonZeroUsize::new(2).unwrap()).graph_finish_time(),
-            18
+            simulated_order(&graph, CostTable::letters(0), 1),
+            (steps(&[(0, "A"), (1, "B"), (3, "C"), (6, "D")]), 10)
         );
     }
 }
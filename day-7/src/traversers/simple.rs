@@ -1,33 +1,60 @@
-use std::{cmp::Reverse, collections::BinaryHeap};
+use std::{cmp::Reverse, collections::BinaryHeap, convert::TryFrom};
 
-use crate::graph::InstructionGraph;
+use crate::graph::{GraphError, InstructionGraph, Traversal};
 
 #[derive(Debug)]
-pub(crate) struct SimpleTraverser {
-    graph: InstructionGraph,
-    to_iterate: BinaryHeap<Reverse<char>>,
+pub(crate) struct SimpleTraverser<'a> {
+    traversal: Traversal<'a>,
+    to_iterate: BinaryHeap<Reverse<String>>,
+    failed: bool,
 }
 
-impl Iterator for SimpleTraverser {
-    type Item = char;
+/// Yields steps in order; if it can't proceed while some steps are still pending,
+/// yields a single error instead
+impl<'a> Iterator for SimpleTraverser<'a> {
+    type Item = Result<String, GraphError>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let to_return = self.to_iterate.pop().map(|reversed| reversed.0);
-
-        if let Some(to_return) = to_return {
-            self.to_iterate
-                .extend(self.graph.visit_node(to_return).into_iter().map(Reverse));
+        if self.failed {
+            return None;
         }
 
-        to_return
+        let result = match self.to_iterate.pop() {
+            Some(Reverse(node)) => self.traversal.visit_node(&node).map(|available| {
+                self.to_iterate.extend(available.into_iter().map(Reverse));
+
+                node
+            }),
+            None => {
+                let remaining = self.traversal.remaining_nodes();
+
+                if remaining.is_empty() {
+                    return None;
+                }
+
+                Err(GraphError::Stuck(remaining))
+            }
+        };
+
+        self.failed = result.is_err();
+
+        Some(result)
     }
 }
 
-impl From<InstructionGraph> for SimpleTraverser {
-    fn from(graph: InstructionGraph) -> Self {
+impl<'a> TryFrom<&'a InstructionGraph> for SimpleTraverser<'a> {
+    type Error = GraphError;
+
+    fn try_from(graph: &'a InstructionGraph) -> Result<Self, Self::Error> {
+        graph.check_acyclic()?;
+
         let to_iterate = graph.get_starting_nodes().map(Reverse).collect();
 
-        SimpleTraverser { graph, to_iterate }
+        Ok(SimpleTraverser {
+            traversal: graph.traversal(),
+            to_iterate,
+            failed: false,
+        })
     }
 }
 
@@ -35,17 +62,74 @@ impl From<InstructionGraph> for SimpleTraverser {
 mod tests {
     use super::*;
 
+    use crate::fixtures::names;
+
     #[test]
     fn test_order() {
         let mut graph = InstructionGraph::new();
 
-        graph.add_link('C', 'F');
-        graph.add_link('C', 'B');
-        graph.add_link('B', 'G');
+        graph.add_link("C", "F");
+        graph.add_link("C", "B");
+        graph.add_link("B", "G");
+
+        assert_eq!(
+            SimpleTraverser::try_from(&graph)
+                .unwrap()
+                .collect::<Result<Vec<_>, _>>(),
+            Ok(names(&["C", "B", "F", "G"]))
+        )
+    }
+
+    #[test]
+    fn test_cycle() {
+        let mut graph = InstructionGraph::new();
+
+        graph.add_link("C", "F");
+        graph.add_link("F", "B");
+        graph.add_link("B", "F");
+
+        assert_eq!(
+            SimpleTraverser::try_from(&graph).unwrap_err(),
+            GraphError::Cycle(names(&["B", "F"]))
+        );
+    }
+
+    #[test]
+    fn test_stuck() {
+        let mut graph = InstructionGraph::new();
+
+        graph.add_link("C", "F");
+        graph.add_link("F", "B");
+        graph.add_link("B", "F");
+
+        // bypass the up-front check to see how traversal itself reports the cycle
+        let mut traverser = SimpleTraverser {
+            traversal: graph.traversal(),
+            to_iterate: graph.get_starting_nodes().map(Reverse).collect(),
+            failed: false,
+        };
+
+        assert_eq!(traverser.next(), Some(Ok("C".to_owned())));
+        assert_eq!(
+            traverser.next(),
+            Some(Err(GraphError::Stuck(names(&["B", "F"]))))
+        );
+        assert_eq!(traverser.next(), None);
+    }
+
+    #[test]
+    fn test_lexicographic_ties() {
+        let mut graph = InstructionGraph::new();
+
+        graph.add_link("build", "test-ui");
+        graph.add_link("build", "test-core");
+        graph.add_link("build", "lint");
 
         assert_eq!(
-            SimpleTraverser::from(graph).collect::<Vec<_>>(),
-            vec!['C', 'B', 'F', 'G']
+            SimpleTraverser::try_from(&graph)
+                .unwrap()
+                .collect::<Result<Vec<_>, _>>(),
+            Ok(names(&["build", "lint", "test-core", "test-ui"]))
         )
     }
 }
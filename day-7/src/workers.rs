@@ -1,23 +1,64 @@
 use std::{
     cmp::{max, Ordering},
-    collections::BinaryHeap,
+    collections::BTreeSet,
     num::NonZeroUsize,
 };
 
-#[derive(Debug, Default, PartialEq, Eq)]
+use serde::Deserialize;
+
+/// Group of workers with the same capability tags
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct WorkerClass {
+    pub count: usize,
+    #[serde(default)]
+    pub tags: BTreeSet<String>,
+}
+
+/// What a step needs to run: number of workers busy with it at once,
+/// each of them having all the tags
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub(crate) struct StepRequirement {
+    #[serde(default)]
+    pub tags: BTreeSet<String>,
+    #[serde(default = "StepRequirement::single_worker")]
+    pub workers: usize,
+}
+
+impl StepRequirement {
+    fn single_worker() -> usize {
+        1
+    }
+}
+
+impl Default for StepRequirement {
+    fn default() -> Self {
+        Self {
+            tags: BTreeSet::new(),
+            workers: Self::single_worker(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub(crate) struct Worker {
+    id: usize,
     free_at: i32,
+    tags: BTreeSet<String>,
 }
 
 impl PartialOrd for Worker {
     fn partial_cmp(&self, other: &Worker) -> Option<Ordering> {
-        other.free_at.partial_cmp(&self.free_at)
+        Some(self.cmp(other))
     }
 }
 
+/// Worker, that is free earlier, goes first; among equally free ones, one with the lowest id
 impl Ord for Worker {
     fn cmp(&self, other: &Worker) -> Ordering {
-        other.free_at.cmp(&self.free_at)
+        other
+            .free_at
+            .cmp(&self.free_at)
+            .then_with(|| other.id.cmp(&self.id))
     }
 }
 
@@ -29,29 +70,108 @@ impl Worker {
     fn schedule_work(&mut self, start_at: i32, work_duration: i32) {
         self.free_at = max(self.free_at, start_at) + work_duration;
     }
+
+    fn can_perform(&self, requirement: &StepRequirement) -> bool {
+        requirement.tags.is_subset(&self.tags)
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub(crate) struct Pool {
-    workers: BinaryHeap<Worker>,
+    workers: Vec<Worker>,
 }
 
 impl Pool {
     pub fn new(n_workers: NonZeroUsize) -> Self {
-        let workers = (0..n_workers.get()).map(|_| Worker::default()).collect();
+        let workers = (0..n_workers.get())
+            .map(|id| Worker {
+                id,
+                ..Default::default()
+            })
+            .collect();
 
         Self { workers }
     }
 
-    pub fn take_work(&mut self, start_at: i32, work_duration: i32) -> i32 {
-        let mut worker = self.workers.pop().unwrap();
+    /// Workers of the given classes, numbered in order; `None` if there are no workers at all
+    pub fn from_classes(classes: &[WorkerClass]) -> Option<Self> {
+        let workers = classes
+            .iter()
+            .flat_map(|class| (0..class.count).map(move |_| class.tags.clone()))
+            .enumerate()
+            .map(|(id, tags)| Worker {
+                id,
+                free_at: 0,
+                tags,
+            })
+            .collect::<Vec<_>>();
+
+        if workers.is_empty() {
+            None
+        } else {
+            Some(Self { workers })
+        }
+    }
 
-        worker.schedule_work(start_at, work_duration);
-        let will_free = worker.free_at();
+    pub fn size(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// Whether the pool has enough workers for the step at all
+    pub fn can_perform(&self, requirement: &StepRequirement) -> bool {
+        let capable = self
+            .workers
+            .iter()
+            .filter(|worker| worker.can_perform(requirement))
+            .count();
+
+        capable >= requirement.workers
+    }
 
-        self.workers.push(worker);
+    /// Ids of workers able to start the step at `now`, if there are enough of them
+    pub fn idle_workers(&self, now: i32, requirement: &StepRequirement) -> Option<Vec<usize>> {
+        let mut idle = self
+            .workers
+            .iter()
+            .filter(|worker| worker.free_at() <= now && worker.can_perform(requirement))
+            .collect::<Vec<_>>();
+
+        if idle.len() < requirement.workers {
+            return None;
+        }
+
+        idle.sort_by(|a, b| b.cmp(a));
 
-        will_free
+        Some(
+            idle.into_iter()
+                .take(requirement.workers)
+                .map(|worker| worker.id)
+                .collect(),
+        )
+    }
+
+    /// Give work to the given workers at once, returning its finish time
+    pub fn assign_to(&mut self, ids: &[usize], start_at: i32, work_duration: i32) -> i32 {
+        let start_at = ids
+            .iter()
+            .map(|&id| self.workers[id].free_at())
+            .fold(start_at, max);
+
+        for &id in ids {
+            self.workers[id].schedule_work(start_at, work_duration);
+        }
+
+        start_at + work_duration
+    }
+
+    /// Give work to the first free worker, returning its id (counted from zero) and finish time
+    #[cfg(test)]
+    pub fn assign_work(&mut self, start_at: i32, work_duration: i32) -> (usize, i32) {
+        let worker = self.workers.iter_mut().max().unwrap();
+
+        worker.schedule_work(start_at, work_duration);
+
+        (worker.id, worker.free_at())
     }
 
     pub fn free_at(&self) -> i32 {
@@ -115,34 +235,95 @@ mod tests {
         // 0, 0, 0
         assert_eq!(pool.free_at(), 0);
 
-        pool.take_work(0, 10);
+        pool.assign_work(0, 10);
         // three workers with times:
         // 10, 0, 0
         assert_eq!(pool.free_at(), 10);
 
-        pool.take_work(0, 10);
+        pool.assign_work(0, 10);
         // three workers with times:
         // 10, 10, 0
         assert_eq!(pool.free_at(), 10);
 
-        pool.take_work(0, 10);
+        pool.assign_work(0, 10);
         // three workers with times:
         // 10, 10, 10
         assert_eq!(pool.free_at(), 10);
 
-        pool.take_work(0, 15);
+        pool.assign_work(0, 15);
         // three workers with times:
         // 25, 10, 10
         assert_eq!(pool.free_at(), 25);
 
-        pool.take_work(0, 5);
+        pool.assign_work(0, 5);
         // three workers with times:
         // 25, 15, 10
         assert_eq!(pool.free_at(), 25);
 
-        pool.take_work(100, 5);
+        pool.assign_work(100, 5);
         // three workers with times:
         // 25, 15, 105
         assert_eq!(pool.free_at(), 105);
     }
+
+    #[test]
+    fn test_assign_work() {
+        let mut pool = Pool::new(NonZeroUsize::new(2).unwrap());
+
+        // equally free workers are taken in the id order
+        assert_eq!(pool.assign_work(0, 10), (0, 10));
+        assert_eq!(pool.assign_work(0, 5), (1, 5));
+        assert_eq!(pool.assign_work(0, 5), (1, 10));
+        assert_eq!(pool.assign_work(0, 1), (0, 11));
+    }
+
+    fn tags(tags: &[&str]) -> BTreeSet<String> {
+        tags.iter().map(|tag| (*tag).to_owned()).collect()
+    }
+
+    #[test]
+    fn test_worker_classes() {
+        let mut pool = Pool::from_classes(&[
+            WorkerClass {
+                count: 2,
+                tags: tags(&["linux"]),
+            },
+            WorkerClass {
+                count: 1,
+                tags: tags(&["linux", "signer"]),
+            },
+        ])
+        .unwrap();
+
+        let sign = StepRequirement {
+            tags: tags(&["signer"]),
+            workers: 1,
+        };
+        let pair = StepRequirement {
+            tags: tags(&["linux"]),
+            workers: 2,
+        };
+
+        assert_eq!(pool.size(), 3);
+        assert!(pool.can_perform(&sign));
+        assert!(!pool.can_perform(&StepRequirement {
+            workers: 2,
+            ..sign.clone()
+        }));
+
+        assert_eq!(pool.idle_workers(0, &sign), Some(vec![2]));
+        assert_eq!(pool.idle_workers(0, &pair), Some(vec![0, 1]));
+
+        assert_eq!(pool.assign_to(&[0, 1], 0, 10), 10);
+        assert_eq!(pool.idle_workers(5, &pair), None);
+        // signer is the only idle one, and it's capable of linux work too
+        assert_eq!(
+            pool.idle_workers(5, &StepRequirement::default()),
+            Some(vec![2])
+        );
+        // worker, that has been idle for longer, goes first
+        assert_eq!(pool.idle_workers(10, &pair), Some(vec![2, 0]));
+
+        assert!(Pool::from_classes(&[]).is_none());
+    }
 }
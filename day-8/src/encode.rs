@@ -0,0 +1,144 @@
+use std::fmt::Write;
+
+use crate::node::{Node, Visit};
+
+fn join(numbers: &[usize], separator: &str) -> String {
+    numbers
+        .iter()
+        .map(usize::to_string)
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+impl Node {
+    /// Space-separated header, children and metadata, as in the puzzle input
+    pub(crate) fn encode(&self) -> String {
+        let mut numbers = Vec::new();
+
+        self.walk(|visit, node, _| match visit {
+            Visit::Enter => numbers.extend(&[node.children().len(), node.metadata().len()]),
+            Visit::Exit => numbers.extend(node.metadata()),
+        });
+
+        join(&numbers, " ")
+    }
+
+    /// Compact JSON: `{"metadata":[...],"children":[...]}`
+    pub(crate) fn to_json(&self) -> String {
+        let mut json = String::new();
+
+        self.walk(|visit, node, path| match visit {
+            Visit::Enter => {
+                // all the children but the first are preceded by a comma
+                if path.0.last().is_some_and(|&idx| idx > 1) {
+                    json.push(',');
+                }
+
+                write!(
+                    json,
+                    "{{\"metadata\":[{}],\"children\":[",
+                    join(node.metadata(), ",")
+                )
+                .unwrap();
+            }
+            Visit::Exit => json.push_str("]}"),
+        });
+
+        json
+    }
+
+    /// One line per node, indented by depth: path, metadata and value
+    pub(crate) fn tree_view(&self) -> String {
+        let values = self.values();
+        let mut view = String::new();
+        let mut idx = 0;
+
+        self.walk(|visit, node, path| {
+            if visit == Visit::Enter {
+                writeln!(
+                    view,
+                    "{:indent$}{} metadata=[{}] value={}",
+                    "",
+                    path,
+                    join(node.metadata(), ", "),
+                    values[idx],
+                    indent = path.0.len() * 2
+                )
+                .unwrap();
+
+                idx += 1;
+            }
+        });
+
+        view
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::fixtures::deep_chain;
+
+    const EXAMPLE: &str = "2 3 0 3 10 11 12 1 1 0 1 99 2 1 1 2";
+
+    fn leaf(metadata: Vec<usize>) -> Node {
+        Node::from_parts(Vec::new(), metadata)
+    }
+
+    #[test]
+    fn test_encode() {
+        let node = Node::from_parts(
+            vec![
+                leaf(vec![10, 11, 12]),
+                Node::from_parts(vec![leaf(vec![99])], vec![2]),
+            ],
+            vec![1, 1, 2],
+        );
+
+        assert_eq!(node.encode(), EXAMPLE);
+        assert_eq!(Node::parse(&node.encode()).unwrap().encode(), EXAMPLE);
+    }
+
+    #[test]
+    fn test_encode_deep_chain() {
+        const DEPTH: usize = 1_000_000;
+
+        let input = deep_chain(DEPTH);
+
+        assert_eq!(Node::parse(&input).unwrap().encode(), input);
+    }
+
+    #[test]
+    fn test_to_json() {
+        let node = Node::parse(EXAMPLE).unwrap();
+
+        assert_eq!(
+            node.to_json(),
+            "{\"metadata\":[1,1,2],\"children\":[\
+             {\"metadata\":[10,11,12],\"children\":[]},\
+             {\"metadata\":[2],\"children\":[{\"metadata\":[99],\"children\":[]}]}\
+             ]}"
+        );
+        assert_eq!(
+            leaf(Vec::new()).to_json(),
+            "{\"metadata\":[],\"children\":[]}"
+        );
+    }
+
+    #[test]
+    fn test_tree_view() {
+        let mut node = Node::parse(EXAMPLE).unwrap();
+
+        assert_eq!(
+            node.tree_view(),
+            "\
+root metadata=[1, 1, 2] value=66
+  root.1 metadata=[10, 11, 12] value=33
+  root.2 metadata=[2] value=0
+    root.2.1 metadata=[99] value=99
+"
+        );
+        assert_eq!(node.values()[0], node.value());
+    }
+}
@@ -0,0 +1,10 @@
+use std::iter;
+
+/// Tree, in which every node has a single child and a single metadata entry
+pub(crate) fn deep_chain(depth: usize) -> String {
+    iter::repeat_n("1 1", depth - 1)
+        .chain(iter::once("0 1"))
+        .chain(iter::repeat_n("1", depth))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -1,15 +1,54 @@
 use std::error::Error;
 
-use advent_utils::{get_config, read_file, Part};
+use serde::Deserialize;
 
+use advent_utils::{get_custom_config, read_file, Part};
+
+mod encode;
+#[cfg(test)]
+mod fixtures;
 mod node;
+mod path;
 
 use self::node::Node;
 
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Output {
+    Flat,
+    Json,
+    Tree,
+}
+
+#[derive(Debug, Deserialize)]
+struct Config {
+    input_file: String,
+    part: Part,
+    #[serde(default)]
+    validate: bool,
+    output: Option<Output>,
+}
+
 fn main() -> Result<(), Box<dyn Error>> {
-    let config = get_config()?;
+    let config = get_custom_config::<Config>()?;
     let input_data = read_file(config.input_file)?;
-    let mut node = Node::from_iter(&mut input_data.split_whitespace().map(|n| n.parse().unwrap()))?;
+    let mut node = Node::parse(&input_data)?;
+
+    if config.validate {
+        let invalid = node.invalid_references();
+
+        for reference in &invalid {
+            println!("{}", reference);
+        }
+        println!("{} invalid metadata references", invalid.len());
+    }
+
+    match config.output {
+        Some(Output::Flat) => println!("{}", node.encode()),
+        Some(Output::Json) => println!("{}", node.to_json()),
+        Some(Output::Tree) => print!("{}", node.tree_view()),
+        None => {}
+    }
 
     match config.part {
         Part::One => {
@@ -1,5 +1,7 @@
 use std::{error::Error, fmt};
 
+use crate::path::NodePath;
+
 #[derive(Debug)]
 pub(crate) struct Node {
     children: Vec<Node>,
@@ -7,69 +9,290 @@ pub(crate) struct Node {
     value: Option<usize>,
 }
 
-#[derive(Debug)]
-pub(crate) struct TooFewElementsError;
+/// Metadata entry of a node with children, that doesn't reference any of them
+#[derive(Debug, PartialEq)]
+pub(crate) struct InvalidReference {
+    pub path: NodePath,
+    /// Position of the entry in the node metadata, counted from zero
+    pub position: usize,
+    pub entry: usize,
+    pub children: usize,
+}
 
-impl fmt::Display for TooFewElementsError {
+impl fmt::Display for InvalidReference {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Wrong tree format: too few elements was specified")
+        write!(
+            f,
+            "{}: metadata entry {} references child {}, but there are {} children",
+            self.path, self.position, self.entry, self.children
+        )
     }
 }
 
-impl Error for TooFewElementsError {}
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Visit {
+    Enter,
+    Exit,
+}
 
-macro_rules! read_header {
-    ($iter: expr) => {
-        (
-            $iter.next().ok_or(TooFewElementsError)?,
-            $iter.next().ok_or(TooFewElementsError)?,
-        )
-    };
+/// Part of the node, that is being read
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum NodeField {
+    ChildrenCount,
+    MetadataCount,
+    Metadata,
+}
+
+impl fmt::Display for NodeField {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NodeField::ChildrenCount => write!(f, "children count"),
+            NodeField::MetadataCount => write!(f, "metadata count"),
+            NodeField::Metadata => write!(f, "metadata entry"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) enum TreeParseError {
+    /// Input ran out at the number with this index, counted from zero
+    UnexpectedEnd { index: usize, expected: NodeField },
+    /// Token at the given number index and byte offset is not a number
+    InvalidNumber {
+        index: usize,
+        offset: usize,
+        token: String,
+    },
+    /// Tree ended at the number with this index, but there are `count` more numbers
+    TrailingData { index: usize, count: usize },
+}
+
+impl fmt::Display for TreeParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TreeParseError::UnexpectedEnd { index, expected } => write!(
+                f,
+                "Wrong tree format: input ended at number {}, expected {}",
+                index, expected
+            ),
+            TreeParseError::InvalidNumber {
+                index,
+                offset,
+                token,
+            } => write!(
+                f,
+                "Wrong tree format: number {} at byte {} is not a number: {:?}",
+                index, offset, token
+            ),
+            TreeParseError::TrailingData { index, count } => write!(
+                f,
+                "Wrong tree format: tree ended at number {}, but {} more follow",
+                index, count
+            ),
+        }
+    }
+}
+
+impl Error for TreeParseError {}
+
+/// Numbers of the input along with the index of the next one
+struct Numbers<'a, I> {
+    iter: &'a mut I,
+    index: usize,
+}
+
+impl<'a, I: Iterator<Item = Result<usize, TreeParseError>>> Numbers<'a, I> {
+    fn next(&mut self, expected: NodeField) -> Result<usize, TreeParseError> {
+        let number = self.iter.next().ok_or(TreeParseError::UnexpectedEnd {
+            index: self.index,
+            expected,
+        })??;
+        self.index += 1;
+
+        Ok(number)
+    }
+}
+
+/// Whitespace-separated numbers of the input
+fn tokenize(input: &str) -> impl Iterator<Item = Result<usize, TreeParseError>> + '_ {
+    input
+        .split_whitespace()
+        .enumerate()
+        .map(move |(index, token)| {
+            token.parse().map_err(|_| TreeParseError::InvalidNumber {
+                index,
+                offset: token.as_ptr() as usize - input.as_ptr() as usize,
+                token: token.to_owned(),
+            })
+        })
+}
+
+/// Node, which children are still being read
+struct PartialNode {
+    node: Node,
+    children_left: usize,
+    metadata_count: usize,
+}
+
+impl PartialNode {
+    fn read_header<I: Iterator<Item = Result<usize, TreeParseError>>>(
+        numbers: &mut Numbers<I>,
+    ) -> Result<Self, TreeParseError> {
+        let children = numbers.next(NodeField::ChildrenCount)?;
+        let metadata = numbers.next(NodeField::MetadataCount)?;
+
+        // counts aren't checked against the input yet, so nothing is preallocated for them
+        Ok(Self {
+            node: Node::new(),
+            children_left: children,
+            metadata_count: metadata,
+        })
+    }
 }
 
 impl Node {
-    fn new(children_count: usize, metadata_count: usize) -> Self {
+    /// Tree built by hand, for tests
+    #[cfg(test)]
+    pub(crate) fn from_parts(children: Vec<Node>, metadata: Vec<usize>) -> Self {
         Self {
-            children: Vec::with_capacity(children_count),
-            metadata: Vec::with_capacity(metadata_count),
+            children,
+            metadata,
             value: None,
         }
     }
 
-    pub(crate) fn from_iter(
-        iter: &mut impl Iterator<Item = usize>,
-    ) -> Result<Self, TooFewElementsError> {
-        let (children, metadata) = read_header!(iter);
-
-        let mut node = Self::new(children, metadata);
-        node.parse_children(iter)?;
-        node.parse_metadata(iter)?;
+    pub(crate) fn children(&self) -> &[Node] {
+        &self.children
+    }
 
-        Ok(node)
+    pub(crate) fn metadata(&self) -> &[usize] {
+        &self.metadata
     }
 
-    fn parse_children(
-        &mut self,
-        iter: &mut impl Iterator<Item = usize>,
-    ) -> Result<(), TooFewElementsError> {
-        for _ in 0..self.children.capacity() {
-            self.children.push(Self::from_iter(iter)?);
+    fn new() -> Self {
+        Self {
+            children: Vec::new(),
+            metadata: Vec::new(),
+            value: None,
         }
+    }
 
-        Ok(())
+    /// Parse the whole input, which should hold exactly one tree
+    pub(crate) fn parse(input: &str) -> Result<Self, TreeParseError> {
+        let mut numbers = Numbers {
+            iter: &mut tokenize(input),
+            index: 0,
+        };
+        let node = Self::read_tree(&mut numbers)?;
+
+        match numbers.iter.next() {
+            Some(Err(e)) => Err(e),
+            Some(Ok(_)) => Err(TreeParseError::TrailingData {
+                index: numbers.index,
+                count: 1 + numbers.iter.count(),
+            }),
+            None => Ok(node),
+        }
     }
 
-    fn parse_metadata(
-        &mut self,
+    /// Parse the tree from numbers, that are already parsed;
+    /// whatever follows the tree is left in the iterator
+    #[allow(dead_code)]
+    pub(crate) fn from_iter(
         iter: &mut impl Iterator<Item = usize>,
-    ) -> Result<(), TooFewElementsError> {
-        self.metadata.extend(iter.take(self.metadata.capacity()));
+    ) -> Result<Self, TreeParseError> {
+        Self::read_tree(&mut Numbers {
+            iter: &mut iter.map(Ok),
+            index: 0,
+        })
+    }
+
+    /// Parse the tree with an explicit stack of unfinished nodes,
+    /// so that the depth of the tree is limited by memory only
+    fn read_tree<I: Iterator<Item = Result<usize, TreeParseError>>>(
+        numbers: &mut Numbers<I>,
+    ) -> Result<Self, TreeParseError> {
+        let mut stack = vec![PartialNode::read_header(numbers)?];
 
-        if self.metadata.len() < self.metadata.capacity() {
-            return Err(TooFewElementsError);
+        loop {
+            let top = stack.last_mut().unwrap();
+
+            if top.children_left > 0 {
+                top.children_left -= 1;
+                stack.push(PartialNode::read_header(numbers)?);
+
+                continue;
+            }
+
+            for _ in 0..top.metadata_count {
+                top.node.metadata.push(numbers.next(NodeField::Metadata)?);
+            }
+
+            let node = stack.pop().unwrap().node;
+            match stack.last_mut() {
+                Some(parent) => parent.node.children.push(node),
+                None => return Ok(node),
+            }
         }
+    }
+
+    fn invalid_entries<'a>(
+        &'a self,
+        path: &'a NodePath,
+    ) -> impl Iterator<Item = InvalidReference> + 'a {
+        let children = self.children.len();
+
+        self.metadata
+            .iter()
+            .enumerate()
+            .filter(move |(_, &entry)| children > 0 && (entry == 0 || entry > children))
+            .map(move |(position, &entry)| InvalidReference {
+                path: path.clone(),
+                position,
+                entry,
+                children,
+            })
+    }
+
+    /// Depth-first walk without recursion: every node is visited on entering,
+    /// before its children, and on exiting, after them
+    pub(crate) fn walk(&self, mut visit: impl FnMut(Visit, &Node, &NodePath)) {
+        let mut stack = vec![(self, 0)];
+        let mut path = NodePath::default();
+
+        visit(Visit::Enter, self, &path);
+
+        while let Some((node, next_child)) = stack.last_mut() {
+            match node.children.get(*next_child) {
+                Some(child) => {
+                    *next_child += 1;
+                    path.0.push(*next_child);
+
+                    visit(Visit::Enter, child, &path);
+                    stack.push((child, 0));
+                }
+                None => {
+                    visit(Visit::Exit, node, &path);
+
+                    stack.pop();
+                    path.0.pop();
+                }
+            }
+        }
+    }
+
+    /// Metadata entries of the nodes with children, that reference non-existent ones,
+    /// in pre-order
+    pub(crate) fn invalid_references(&self) -> Vec<InvalidReference> {
+        let mut invalid = Vec::new();
 
-        Ok(())
+        self.walk(|visit, node, path| {
+            if visit == Visit::Enter {
+                invalid.extend(node.invalid_entries(path));
+            }
+        });
+
+        invalid
     }
 
     pub(crate) fn checksum(&self) -> usize {
@@ -78,29 +301,63 @@ impl Node {
             .sum()
     }
 
+    /// Value of every node in pre-order, computed without recursion
+    pub(crate) fn values(&self) -> Vec<usize> {
+        let mut values = Vec::new();
+        // pre-order index of every unfinished node along with its children values
+        let mut pending: Vec<(usize, Vec<usize>)> = Vec::new();
+
+        self.walk(|visit, node, _| match visit {
+            Visit::Enter => {
+                pending.push((values.len(), Vec::new()));
+                values.push(0);
+            }
+            Visit::Exit => {
+                let (idx, children) = pending.pop().unwrap();
+
+                let value = if children.is_empty() {
+                    node.metadata().iter().sum()
+                } else {
+                    node.metadata()
+                        .iter()
+                        .filter_map(|entry| entry.checked_sub(1).and_then(|i| children.get(i)))
+                        .sum()
+                };
+
+                values[idx] = value;
+                if let Some((_, siblings)) = pending.last_mut() {
+                    siblings.push(value);
+                }
+            }
+        });
+
+        values
+    }
+
     pub(crate) fn value(&mut self) -> usize {
         if let Some(value) = self.value {
             return value;
         };
 
-        let metadata = &self.metadata;
-        let children = &mut self.children;
-
-        let value = if children.is_empty() {
-            metadata.iter().sum()
-        } else {
-            metadata
-                .iter()
-                .map(|idx| children.get_mut(*idx - 1).map_or(0, |child| child.value()))
-                .sum()
-        };
-
+        let value = self.values()[0];
         self.value.replace(value);
 
         value
     }
 }
 
+/// Children are dropped iteratively, as the recursive drop would overflow the stack
+/// on deep trees
+impl Drop for Node {
+    fn drop(&mut self) {
+        let mut to_drop = std::mem::take(&mut self.children);
+
+        while let Some(mut node) = to_drop.pop() {
+            to_drop.append(&mut node.children);
+        }
+    }
+}
+
 impl<'a> IntoIterator for &'a Node {
     type Item = &'a Node;
     type IntoIter = NodeIterator<'a>;
@@ -135,6 +392,8 @@ impl<'a> Iterator for NodeIterator<'a> {
 mod tests {
     use super::*;
 
+    use crate::fixtures::deep_chain;
+
     #[test]
     fn test_node_parser() {
         let mut input = "2 3 0 3 10 11 12 1 1 0 1 99 2 1 1 2"
@@ -204,14 +463,73 @@ mod tests {
     fn test_wrong_format() {
         let mut input = "3 0".split_whitespace().map(|n| n.parse().unwrap());
 
-        assert!(Node::from_iter(&mut input).is_err());
+        assert_eq!(
+            Node::from_iter(&mut input).unwrap_err(),
+            TreeParseError::UnexpectedEnd {
+                index: 2,
+                expected: NodeField::ChildrenCount
+            }
+        );
 
         // incomplete root metadata
         let mut input = "2 3 0 3 10 11 12 1 1 0 1 99 2 1 1"
             .split_whitespace()
             .map(|n| n.parse().unwrap());
 
-        assert!(Node::from_iter(&mut input).is_err());
+        let error = Node::from_iter(&mut input).unwrap_err();
+        assert_eq!(
+            error,
+            TreeParseError::UnexpectedEnd {
+                index: 15,
+                expected: NodeField::Metadata
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "Wrong tree format: input ended at number 15, expected metadata entry"
+        );
+
+        let mut input = "1 1 0".split_whitespace().map(|n| n.parse().unwrap());
+
+        assert_eq!(
+            Node::from_iter(&mut input).unwrap_err(),
+            TreeParseError::UnexpectedEnd {
+                index: 3,
+                expected: NodeField::MetadataCount
+            }
+        );
+    }
+
+    #[test]
+    fn test_huge_counts() {
+        assert_eq!(
+            Node::parse("18446744073709551615 0").unwrap_err(),
+            TreeParseError::UnexpectedEnd {
+                index: 2,
+                expected: NodeField::ChildrenCount
+            }
+        );
+        assert_eq!(
+            Node::parse("0 1000000000000").unwrap_err(),
+            TreeParseError::UnexpectedEnd {
+                index: 2,
+                expected: NodeField::Metadata
+            }
+        );
+    }
+
+    #[test]
+    fn test_deep_chain() {
+        const DEPTH: usize = 1_000_000;
+
+        let input = deep_chain(DEPTH);
+
+        let mut node = Node::parse(&input).unwrap();
+
+        assert_eq!(node.into_iter().count(), DEPTH);
+        assert_eq!(node.checksum(), DEPTH);
+        // the leaf sums its metadata, every other node references its only child
+        assert_eq!(node.value(), 1);
     }
 
     #[test]
@@ -252,4 +570,81 @@ mod tests {
         assert_eq!(node.value(), 21);
         assert_eq!(node.value, Some(21));
     }
+
+    #[test]
+    fn test_trailing_data() {
+        assert_eq!(
+            Node::parse("0 1 5 7 8").unwrap_err(),
+            TreeParseError::TrailingData { index: 3, count: 2 }
+        );
+        assert_eq!(
+            Node::parse("0 1 5 7 8").unwrap_err().to_string(),
+            "Wrong tree format: tree ended at number 3, but 2 more follow"
+        );
+        assert!(Node::parse(" 0 1 5\n").is_ok());
+    }
+
+    #[test]
+    fn test_invalid_number() {
+        assert_eq!(
+            Node::parse("1 1 0 x 3 4").unwrap_err(),
+            TreeParseError::InvalidNumber {
+                index: 3,
+                offset: 6,
+                token: "x".to_owned()
+            }
+        );
+        assert_eq!(
+            Node::parse("0 1 5 -1").unwrap_err(),
+            TreeParseError::InvalidNumber {
+                index: 3,
+                offset: 6,
+                token: "-1".to_owned()
+            }
+        );
+    }
+
+    #[test]
+    fn test_zero_reference() {
+        // root references children 0, 1 and 3, only the second one exists
+        let mut node = Node::parse("1 3 0 1 5 0 1 3").unwrap();
+
+        assert_eq!(node.value(), 5);
+        assert_eq!(
+            node.invalid_references(),
+            vec![
+                InvalidReference {
+                    path: NodePath(vec![]),
+                    position: 0,
+                    entry: 0,
+                    children: 1
+                },
+                InvalidReference {
+                    path: NodePath(vec![]),
+                    position: 2,
+                    entry: 3,
+                    children: 1
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_references() {
+        let node = Node::parse("2 3 0 3 10 11 12 1 1 0 1 99 2 1 1 2").unwrap();
+        let invalid = node.invalid_references();
+
+        // C has a single child, but references the second one
+        assert_eq!(invalid.len(), 1);
+        assert_eq!(
+            invalid[0].to_string(),
+            "root.2: metadata entry 0 references child 2, but there are 1 children"
+        );
+
+        // leaves' metadata isn't a reference
+        assert!(Node::parse("0 2 0 7")
+            .unwrap()
+            .invalid_references()
+            .is_empty());
+    }
 }
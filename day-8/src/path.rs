@@ -0,0 +1,29 @@
+use std::fmt;
+
+/// Position of a node in the tree: child indices from the root, counted from 1,
+/// as metadata entries reference them
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct NodePath(pub Vec<usize>);
+
+impl fmt::Display for NodePath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "root")?;
+
+        for idx in &self.0 {
+            write!(f, ".{}", idx)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        assert_eq!(NodePath::default().to_string(), "root");
+        assert_eq!(NodePath(vec![2, 1]).to_string(), "root.2.1");
+    }
+}